@@ -0,0 +1,372 @@
+//! Runs any subset of days through their [`aoc2016::Solution`] impls.
+//!
+//! ```text
+//! aoc all                 run every day on dNN/input.txt
+//! aoc 12 23               run days 12 and 23
+//! aoc all --sample        run every day on dNN/sample.txt
+//! aoc 8 --input FILE      run day 8 on FILE, or on stdin if FILE is `-`
+//! aoc all --json          write the results as a JSON array, one object per day
+//! aoc 14 --debug          also show extra output of the day, like d14's list of keys
+//! aoc verify [DAY...]     compare with the answers in dNN/answers.txt (all days by default)
+//! aoc bench 5 14 --runs 20 --format csv
+//!                         time parse, part1 and part2 of days 5 and 14, 20 times each
+//! ```
+
+#![feature(bstr)]
+
+use aoc2016::Json;
+use aoc2016::bench::{Format, HumanDuration, write_reports};
+use aoc2016::solution::{Answers, Day, InputSource, Report, print_answer, print_debug};
+use std::process::ExitCode;
+use std::time::Duration;
+use std::{env, io};
+
+// every day is a binary of its own as well, so their `main` functions are unused in here.
+#[allow(dead_code)]
+#[path = "../d01/d01.rs"]
+mod d01;
+#[allow(dead_code)]
+#[path = "../d02/d02.rs"]
+mod d02;
+#[allow(dead_code)]
+#[path = "../d03/d03.rs"]
+mod d03;
+#[allow(dead_code)]
+#[path = "../d04/d04.rs"]
+mod d04;
+#[allow(dead_code)]
+#[path = "../d05/d05.rs"]
+mod d05;
+#[allow(dead_code)]
+#[path = "../d06/d06.rs"]
+mod d06;
+#[allow(dead_code)]
+#[path = "../d07/d07.rs"]
+mod d07;
+#[allow(dead_code)]
+#[path = "../d08/d08.rs"]
+mod d08;
+#[allow(dead_code)]
+#[path = "../d09/d09.rs"]
+mod d09;
+#[allow(dead_code)]
+#[path = "../d10/d10.rs"]
+mod d10;
+#[allow(dead_code)]
+#[path = "../d11/d11.rs"]
+mod d11;
+#[allow(dead_code)]
+#[path = "../d12/d12.rs"]
+mod d12;
+#[allow(dead_code)]
+#[path = "../d13/d13.rs"]
+mod d13;
+#[allow(dead_code, unused_attributes)]
+#[path = "../d14/d14.rs"]
+mod d14;
+#[allow(dead_code)]
+#[path = "../d15/d15.rs"]
+mod d15;
+#[allow(dead_code)]
+#[path = "../d16/d16.rs"]
+mod d16;
+#[allow(dead_code)]
+#[path = "../d17/d17.rs"]
+mod d17;
+#[allow(dead_code)]
+#[path = "../d18/d18.rs"]
+mod d18;
+#[allow(dead_code)]
+#[path = "../d19/d19.rs"]
+mod d19;
+#[allow(dead_code)]
+#[path = "../d20/d20.rs"]
+mod d20;
+#[allow(dead_code)]
+#[path = "../d21/d21.rs"]
+mod d21;
+#[allow(dead_code)]
+#[path = "../d22/d22.rs"]
+mod d22;
+#[allow(dead_code)]
+#[path = "../d23/d23.rs"]
+mod d23;
+#[allow(dead_code)]
+#[path = "../d24/d24.rs"]
+mod d24;
+
+const DAYS: [Day; 24] = [
+    Day::of::<d01::Day01>(),
+    Day::of::<d02::Day02>(),
+    Day::of::<d03::Day03>(),
+    Day::of::<d04::Day04>(),
+    Day::of::<d05::Day05>(),
+    Day::of::<d06::Day06>(),
+    Day::of::<d07::Day07>(),
+    Day::of::<d08::Day08>(),
+    Day::of::<d09::Day09>(),
+    Day::of::<d10::Day10>(),
+    Day::of::<d11::Day11>(),
+    Day::of::<d12::Day12>(),
+    Day::of::<d13::Day13>(),
+    Day::of::<d14::Day14>(),
+    Day::of::<d15::Day15>(),
+    Day::of::<d16::Day16>(),
+    Day::of::<d17::Day17>(),
+    Day::of::<d18::Day18>(),
+    Day::of::<d19::Day19>(),
+    Day::of::<d20::Day20>(),
+    Day::of::<d21::Day21>(),
+    Day::of::<d22::Day22>(),
+    Day::of::<d23::Day23>(),
+    Day::of::<d24::Day24>(),
+];
+
+const USAGE: &str = "\
+usage: aoc (all | DAY...) [--sample | --input FILE|-] [--json] [--debug]
+       aoc verify [DAY...] [--sample]
+       aoc bench (all | DAY...) [--sample | --input FILE|-] [--runs N] [--format text|csv|json]";
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+enum Mode {
+    #[default]
+    Run,
+    Verify,
+    Bench,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+struct Args {
+    mode: Mode,
+    days: Vec<u8>,
+    input: InputSource,
+    runs: usize,
+    format: Format,
+    json: bool,
+    debug: bool,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut result = Args {
+        runs: 10,
+        ..Args::default()
+    };
+    let mut args = args.into_iter().peekable();
+    if args.next_if(|a| a == "verify").is_some() {
+        result.mode = Mode::Verify;
+    } else if args.next_if(|a| a == "bench").is_some() {
+        result.mode = Mode::Bench;
+    }
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "all" => result.days.extend(DAYS.iter().map(|d| d.day)),
+            "--sample" | "-s" => result.input = InputSource::Sample,
+            "--input" | "-i" => {
+                result.input = match args.next().as_deref() {
+                    Some("-") => InputSource::Stdin,
+                    Some(path) => InputSource::File(path.into()),
+                    None => return Err("--input needs a file name or '-'".to_string()),
+                };
+            }
+            "--runs" | "-n" if result.mode == Mode::Bench => {
+                result.runs = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .filter(|&n| n > 0)
+                    .ok_or("--runs needs a positive number")?;
+            }
+            "--format" | "-f" if result.mode == Mode::Bench => {
+                result.format = args.next().ok_or("--format needs a value")?.parse()?;
+            }
+            "--json" if result.mode == Mode::Run => result.json = true,
+            "--debug" if result.mode == Mode::Run => result.debug = true,
+            _ => {
+                let day = arg
+                    .trim_start_matches('d')
+                    .parse()
+                    .map_err(|_| format!("unknown argument '{arg}'"))?;
+                if !DAYS.iter().any(|d| d.day == day) {
+                    return Err(format!("there is no day {day}"));
+                }
+                result.days.push(day);
+            }
+        }
+    }
+    let custom_input = matches!(result.input, InputSource::File(_) | InputSource::Stdin);
+    match result.mode {
+        Mode::Run | Mode::Bench if result.days.is_empty() => {
+            return Err("no days given".to_string());
+        }
+        Mode::Run | Mode::Bench if custom_input && result.days.len() != 1 => {
+            return Err("--input can only be used with a single day".to_string());
+        }
+        Mode::Verify if custom_input => {
+            return Err("there are no recorded answers for --input".to_string());
+        }
+        Mode::Verify if result.days.is_empty() => result.days.extend(DAYS.iter().map(|d| d.day)),
+        _ => {}
+    }
+    Ok(result)
+}
+
+fn print_report(report: &Report, debug: bool) {
+    println!("Day {:02}", report.day);
+    print_answer(1, &report.part1);
+    print_answer(2, &report.part2);
+    if debug {
+        print_debug(report.debug.as_ref());
+    }
+    println!(
+        "parse {}, part1 {}, part2 {}",
+        HumanDuration(report.parse_time),
+        HumanDuration(report.part1_time),
+        HumanDuration(report.part2_time),
+    );
+}
+
+fn day(day: u8) -> &'static Day {
+    DAYS.iter().find(|d| d.day == day).unwrap()
+}
+
+/// Prints the answers of all requested days, or writes them as JSON. Returns whether all of them
+/// could be solved.
+fn run(args: &Args) -> bool {
+    let mut total = Duration::ZERO;
+    let mut ok = true;
+    let mut json = Vec::new();
+    for (i, &nr) in args.days.iter().enumerate() {
+        if i > 0 && !args.json {
+            println!();
+        }
+        let result = args
+            .input
+            .read(nr)
+            .map_err(|e| format!("could not read input: {e}"))
+            .and_then(|input| {
+                (day(nr).solve)(&input, args.debug).map_err(|e| format!("invalid input: {e}"))
+            });
+        match result {
+            Ok(report) if args.json => json.push(report.to_json()),
+            Ok(report) => {
+                print_report(&report, args.debug);
+                total += report.total_time();
+            }
+            Err(e) => {
+                eprintln!("Day {nr:02}: {e}");
+                if args.json {
+                    json.push(Json::object([
+                        ("day", Json::from(nr)),
+                        ("error", Json::from(e)),
+                    ]));
+                }
+                ok = false;
+            }
+        }
+    }
+    if args.json {
+        println!("{}", Json::Array(json));
+    } else if args.days.len() > 1 {
+        println!("\nTotal: {}", HumanDuration(total));
+    }
+    ok
+}
+
+/// Checks all requested days against their recorded answers, running only the parts that have
+/// one. Days without any are skipped. Returns whether there were no mismatches or errors.
+fn verify(args: &Args) -> bool {
+    let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+    for &nr in &args.days {
+        let answers = match Answers::load(nr, &args.input) {
+            Ok(Some(answers)) if answers.part1.is_some() || answers.part2.is_some() => answers,
+            Ok(_) => {
+                println!("Day {nr:02}: skipped, no recorded answers");
+                skipped += 1;
+                continue;
+            }
+            Err(e) => {
+                println!("Day {nr:02}: FAILED, {e}");
+                failed += 1;
+                continue;
+            }
+        };
+        let result = args
+            .input
+            .read(nr)
+            .map_err(|e| format!("could not read input: {e}"))
+            .and_then(|input| {
+                (day(nr).verify)(&input, &answers).map_err(|e| format!("invalid input: {e}"))
+            });
+        match result {
+            Ok(result) => {
+                let only = match result.checked[..] {
+                    [part] => format!(", part{part} only"),
+                    _ => String::new(),
+                };
+                if result.mismatches.is_empty() {
+                    println!("Day {nr:02}: ok ({}{only})", HumanDuration(result.time));
+                    passed += 1;
+                } else {
+                    println!("Day {nr:02}: FAILED{only}");
+                    for m in result.mismatches {
+                        println!("  {m}");
+                    }
+                    failed += 1;
+                }
+            }
+            Err(e) => {
+                println!("Day {nr:02}: FAILED, {e}");
+                failed += 1;
+            }
+        }
+    }
+    println!("\n{passed} passed, {failed} failed, {skipped} skipped");
+    failed == 0
+}
+
+/// Times all requested days and writes the statistics to stdout once all of them are done.
+fn bench(args: &Args) -> bool {
+    let mut reports = Vec::new();
+    let mut ok = true;
+    for &nr in &args.days {
+        eprintln!("Day {nr:02}: {} runs...", args.runs);
+        let report = args
+            .input
+            .read(nr)
+            .map_err(|e| format!("could not read input: {e}"))
+            .and_then(|input| {
+                (day(nr).bench)(&input, args.runs).map_err(|e| format!("invalid input: {e}"))
+            });
+        match report {
+            Ok(report) => reports.push(report),
+            Err(e) => {
+                eprintln!("Day {nr:02}: {e}");
+                ok = false;
+            }
+        }
+    }
+    if let Err(e) = write_reports(io::stdout().lock(), &reports, args.format) {
+        eprintln!("could not write results: {e}");
+        ok = false;
+    }
+    ok
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let ok = match args.mode {
+        Mode::Run => run(&args),
+        Mode::Verify => verify(&args),
+        Mode::Bench => bench(&args),
+    };
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -1,155 +1,120 @@
+use aoc2016::solution::{self, Solution};
+use aoc2016::{ParseError, Turn, Turtle};
 use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::Display;
 use std::iter::{once, repeat_n};
 use std::str::FromStr;
-use vecmath::{vec2_add, vec2_scale};
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
-enum Turn {
-    Straight,
-    Left,
-    Right,
-}
-
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
-struct Instruction {
+pub struct Instruction {
     turn: Turn,
     dist: u32,
 }
 
-impl FromStr for Turn {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "L" => Ok(Turn::Left),
-            "R" => Ok(Turn::Right),
-            _ => Err(()),
-        }
-    }
-}
-
 impl FromStr for Instruction {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() < 2 {
-            return Err(());
-        }
+        let mut chars = s.chars();
+        let turn = chars
+            .next()
+            .and_then(Turn::from_char)
+            .ok_or_else(|| ParseError::at(s, s, "L or R"))?;
+        let dist = chars.as_str();
         Ok(Instruction {
-            turn: s[..1].parse()?,
-            dist: s[1..].parse().map_err(|_| ())?,
+            turn,
+            dist: dist
+                .parse()
+                .map_err(|_| ParseError::at(s, dist, "a distance"))?,
         })
     }
 }
 
-fn parse_instructions(s: &str) -> Result<Vec<Instruction>, ()> {
+fn parse_instructions(s: &str) -> Result<Vec<Instruction>, ParseError> {
     s.split(',')
         .map(str::trim)
         .filter(|s| !s.is_empty())
-        .map(|i| i.parse())
+        .map(|i| i.parse::<Instruction>().map_err(|e| e.within(s, i)))
         .collect::<Result<_, _>>()
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
+fn execute(mut state: Turtle, ins: Instruction) -> Turtle {
+    state.turn(ins.turn);
+    state.forward(ins.dist as i32);
+    state
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
-struct State {
-    dir: Direction,
-    pos: [i32; 2],
+/// Where the turtle is with north as +y, the way this day has always reported positions.
+/// [`Turtle`] uses screen coordinates, where north is -y.
+fn position(turtle: &Turtle) -> [i32; 2] {
+    let [x, y] = turtle.pos;
+    [x, -y]
 }
 
-impl Default for State {
-    fn default() -> Self {
-        Self {
-            dir: Direction::North,
-            pos: [0, 0],
-        }
-    }
+fn dist_from_0(pos: [i32; 2]) -> i32 {
+    pos[0].abs() + pos[1].abs()
 }
 
-fn turn(direction: Direction, turn: Turn) -> Direction {
-    match (direction, turn) {
-        (dir, Turn::Straight) => dir,
-        (Direction::North, Turn::Right) => Direction::East,
-        (Direction::East, Turn::Right) => Direction::South,
-        (Direction::South, Turn::Right) => Direction::West,
-        (Direction::West, Turn::Right) => Direction::North,
-        (Direction::North, Turn::Left) => Direction::West,
-        (Direction::East, Turn::Left) => Direction::North,
-        (Direction::South, Turn::Left) => Direction::East,
-        (Direction::West, Turn::Left) => Direction::South,
-    }
-}
+pub struct Day01;
 
-impl Direction {
-    fn vec(&self) -> [i32; 2] {
-        match self {
-            Direction::North => [0, 1],
-            Direction::East => [1, 0],
-            Direction::South => [0, -1],
-            Direction::West => [-1, 0],
-        }
-    }
-}
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+    type Input<'i> = Vec<Instruction>;
 
-fn execute(mut state: State, ins: Instruction) -> State {
-    state.dir = turn(state.dir, ins.turn);
-    state.pos = vec2_add(state.pos, vec2_scale(state.dir.vec(), ins.dist as i32));
-    state
-}
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        Ok(parse_instructions(input)?)
+    }
 
-fn dist_from_0(pos: [i32; 2]) -> i32 {
-    pos[0].abs() + pos[1].abs()
-}
+    fn part1(instructions: &Self::Input<'_>) -> impl Display {
+        let final_state = instructions
+            .iter()
+            .copied()
+            .fold(Turtle::default(), execute);
+        dist_from_0(position(&final_state))
+    }
 
-fn main() {
-    let input = include_str!("d01.txt");
-    // let input = "R8, R4, R4, R8";
-    let instructions = parse_instructions(input).unwrap();
-    let final_state = instructions.iter().copied().fold(State::default(), execute);
-    let dist = dist_from_0(final_state.pos);
-    println!("Part1: {}", dist);
-
-    let final_state = instructions
-        .into_iter()
-        // We have to do this because when paths cross, it also counts. Not just when we _land_
-        // on an already visited place. So I just split the instruction into single-length steps.
-        .flat_map(|ins| {
-            once(Instruction { dist: 1, ..ins }).chain(repeat_n(
-                Instruction {
-                    turn: Turn::Straight,
-                    dist: 1,
-                },
-                if ins.dist > 1 {
-                    (ins.dist as i32 - 1) as usize
-                } else {
-                    0
+    fn part2(instructions: &Self::Input<'_>) -> impl Display {
+        let first_revisit = instructions
+            .iter()
+            // We have to do this because when paths cross, it also counts. Not just when we _land_
+            // on an already visited place. So I just split the instruction into single-length steps.
+            .flat_map(|&ins| {
+                once(Instruction { dist: 1, ..ins }).chain(repeat_n(
+                    Instruction {
+                        turn: Turn::Straight,
+                        dist: 1,
+                    },
+                    if ins.dist > 1 {
+                        (ins.dist as i32 - 1) as usize
+                    } else {
+                        0
+                    },
+                ))
+            })
+            .scan(
+                (HashSet::from([[0, 0]]), Turtle::default()),
+                |(visited, state), ins| {
+                    *state = execute(*state, ins);
+                    if visited.contains(&state.pos) {
+                        return Some(Some(*state));
+                    }
+                    visited.insert(state.pos);
+                    Some(None)
                 },
-            ))
-        })
-        .scan(
-            (HashSet::from([[0, 0]]), State::default()),
-            |(visited, state), ins| {
-                *state = execute(*state, ins);
-                if visited.contains(&state.pos) {
-                    return Some(Some(*state));
-                }
-                visited.insert(state.pos);
-                Some(None)
-            },
+            )
+            .flatten()
+            .next();
+        // the path may never cross itself
+        first_revisit.map_or_else(
+            || "none".to_string(),
+            |s| dist_from_0(position(&s)).to_string(),
         )
-        .find(Option::is_some)
-        .unwrap()
-        .unwrap();
+    }
+}
 
-    let dist = dist_from_0(final_state.pos);
-    println!("Part2: {}", dist);
+fn main() {
+    solution::main::<Day01>();
 }
 
 #[cfg(test)]
@@ -172,10 +137,10 @@ mod tests {
                 dist: 69,
             })
         );
-        assert_eq!("X123".parse::<Instruction>(), Err(()));
-        assert_eq!("L".parse::<Instruction>(), Err(()));
-        assert_eq!("".parse::<Instruction>(), Err(()));
-        assert_eq!("L123L".parse::<Instruction>(), Err(()));
+        assert!("X123".parse::<Instruction>().is_err());
+        assert!("L".parse::<Instruction>().is_err());
+        assert!("".parse::<Instruction>().is_err());
+        assert!("L123L".parse::<Instruction>().is_err());
     }
 
     #[test]
@@ -214,29 +179,43 @@ mod tests {
                 }
             ])
         );
+        assert_eq!(
+            parse_instructions("L1, R2,\nR3, X4"),
+            Err(ParseError::new(2, 5, "L or R", "'X4'"))
+        );
+    }
+
+    #[test]
+    fn test_no_revisit() {
+        assert_eq!(Day01::part2(&vec![]).to_string(), "none");
+        let instructions = parse_instructions("R8, R4, R4, R8").unwrap();
+        assert_eq!(Day01::part2(&instructions).to_string(), "4");
     }
 
     #[test]
     fn test_example1() {
         let instructions = parse_instructions("R2, L3").unwrap();
-        let result = instructions.into_iter().fold(State::default(), execute);
-        assert_eq!(result.pos, [2, 3]);
-        assert_eq!(dist_from_0(result.pos), 5);
+        let result = instructions.into_iter().fold(Turtle::default(), execute);
+        assert_eq!(position(&result), [2, 3]);
+        assert_eq!(result.pos, [2, -3]);
+        assert_eq!(dist_from_0(position(&result)), 5);
     }
 
     #[test]
     fn test_example2() {
         let instructions = parse_instructions("R2, R2, R2").unwrap();
-        let result = instructions.into_iter().fold(State::default(), execute);
-        assert_eq!(result.pos, [0, -2]);
-        assert_eq!(dist_from_0(result.pos), 2);
+        let result = instructions.into_iter().fold(Turtle::default(), execute);
+        assert_eq!(position(&result), [0, -2]);
+        assert_eq!(result.pos, [0, 2]);
+        assert_eq!(dist_from_0(position(&result)), 2);
     }
 
     #[test]
     fn test_example3() {
         let instructions = parse_instructions("R5, L5, R5, R3").unwrap();
-        let result = instructions.into_iter().fold(State::default(), execute);
-        assert_eq!(result.pos, [10, 2]);
-        assert_eq!(dist_from_0(result.pos), 12);
+        let result = instructions.into_iter().fold(Turtle::default(), execute);
+        assert_eq!(position(&result), [10, 2]);
+        assert_eq!(result.pos, [10, -2]);
+        assert_eq!(dist_from_0(position(&result)), 12);
     }
 }
@@ -1,42 +1,26 @@
-use vecmath::vec2_add;
-
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-fn parse_dirs(s: &str) -> impl Iterator<Item = Direction> {
-    s.chars().map(|c| match c {
-        'U' => Direction::Up,
-        'R' => Direction::Right,
-        'D' => Direction::Down,
-        'L' => Direction::Left,
-        _ => panic!("unexpected input"),
-    })
-}
-
-impl Direction {
-    fn vec(&self) -> [i32; 2] {
-        match self {
-            Direction::Up => [0, -1],
-            Direction::Right => [1, 0],
-            Direction::Down => [0, 1],
-            Direction::Left => [-1, 0],
-        }
-    }
+use aoc2016::parse::parse_lines;
+use aoc2016::solution::{self, Solution};
+use aoc2016::{Direction, ParseError};
+use std::error::Error;
+use std::fmt::Display;
+
+fn parse_dirs(s: &str) -> Result<Vec<Direction>, ParseError> {
+    let line = s.trim();
+    line.char_indices()
+        .map(|(i, c)| {
+            Direction::from_char(c).ok_or_else(|| ParseError::at(s, &line[i..], "U, D, L or R"))
+        })
+        .collect()
 }
 
 fn get_code<'s>(
-    lines: impl IntoIterator<Item = &'s str>,
+    lines: impl IntoIterator<Item = &'s [Direction]>,
     start: [i32; 2],
     move_numpad: impl Fn([i32; 2], Direction) -> [i32; 2] + Copy,
     number_at: impl Fn([i32; 2]) -> char,
 ) -> impl Iterator<Item = char> {
     lines.into_iter().scan(start, move |fingy, l| {
-        *fingy = parse_dirs(l).fold(*fingy, move_numpad);
+        *fingy = l.iter().copied().fold(*fingy, move_numpad);
         Some(number_at(*fingy))
     })
 }
@@ -45,7 +29,7 @@ mod part1 {
     use super::*;
 
     fn move_numpad(pos: [i32; 2], dir: Direction) -> [i32; 2] {
-        let [x, y] = vec2_add(pos, dir.vec());
+        let [x, y] = dir.step(pos, 1);
         [x.clamp(0, 2), y.clamp(0, 2)]
     }
 
@@ -54,7 +38,7 @@ mod part1 {
     }
 
     pub fn get_code<'s>(
-        lines: impl IntoIterator<Item = &'s str> + 's,
+        lines: impl IntoIterator<Item = &'s [Direction]> + 's,
     ) -> impl Iterator<Item = char> {
         super::get_code(lines, [1, 1], move_numpad, number_at)
     }
@@ -65,8 +49,8 @@ mod part1 {
 
         #[test]
         fn test_code() {
-            let instructions = ["ULL", "RRDDD", "LURDL", "UUUUD"];
-            let code: Vec<_> = get_code(instructions).collect();
+            let instructions = ["ULL", "RRDDD", "LURDL", "UUUUD"].map(|l| parse_dirs(l).unwrap());
+            let code: Vec<_> = get_code(instructions.iter().map(Vec::as_slice)).collect();
             assert_eq!(code, ['1', '9', '8', '5']);
             assert_eq!(code.into_iter().collect::<String>(), "1985");
         }
@@ -86,7 +70,7 @@ mod part2 {
     }
 
     fn move_numpad(pos: [i32; 2], dir: Direction) -> [i32; 2] {
-        let new_pos = vec2_add(pos, dir.vec());
+        let new_pos = dir.step(pos, 1);
         if is_inside(new_pos) { new_pos } else { pos }
     }
 
@@ -101,7 +85,7 @@ mod part2 {
     }
 
     pub fn get_code<'s>(
-        lines: impl IntoIterator<Item = &'s str> + 's,
+        lines: impl IntoIterator<Item = &'s [Direction]> + 's,
     ) -> impl Iterator<Item = char> {
         super::get_code(lines, [0, 2], move_numpad, number_at)
     }
@@ -112,20 +96,34 @@ mod part2 {
 
         #[test]
         fn test_code() {
-            let instructions = ["ULL", "RRDDD", "LURDL", "UUUUD"];
-            let code: Vec<_> = get_code(instructions).collect();
+            let instructions = ["ULL", "RRDDD", "LURDL", "UUUUD"].map(|l| parse_dirs(l).unwrap());
+            let code: Vec<_> = get_code(instructions.iter().map(Vec::as_slice)).collect();
             assert_eq!(code.into_iter().collect::<String>(), "5DB3");
         }
     }
 }
 
-fn main() {
-    let input = include_str!("d02.txt");
-    let code: String = part1::get_code(input.lines()).collect();
-    println!("Part1: {}", code);
+pub struct Day02;
 
-    let code: String = part2::get_code(input.lines()).collect();
-    println!("Part2: {}", code);
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+    type Input<'i> = Vec<Vec<Direction>>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        Ok(parse_lines(input, parse_dirs)?)
+    }
+
+    fn part1(lines: &Self::Input<'_>) -> impl Display {
+        part1::get_code(lines.iter().map(Vec::as_slice)).collect::<String>()
+    }
+
+    fn part2(lines: &Self::Input<'_>) -> impl Display {
+        part2::get_code(lines.iter().map(Vec::as_slice)).collect::<String>()
+    }
+}
+
+fn main() {
+    solution::main::<Day02>();
 }
 
 #[cfg(test)]
@@ -135,11 +133,16 @@ mod tests {
     #[test]
     fn test_parse() {
         use Direction::*;
-        assert_eq!(parse_dirs("").collect::<Vec<_>>(), vec![]);
-        assert_eq!(parse_dirs("L").collect::<Vec<_>>(), vec![Left]);
+        assert_eq!(parse_dirs(""), Ok(vec![]));
+        assert_eq!(parse_dirs("L"), Ok(vec![West]));
+        assert_eq!(parse_dirs("LRUD "), Ok(vec![West, East, North, South]));
+        assert_eq!(
+            parse_dirs(" LR UD"),
+            Err(ParseError::new(1, 4, "U, D, L or R", "whitespace"))
+        );
         assert_eq!(
-            parse_dirs("LRUD").collect::<Vec<_>>(),
-            vec![Left, Right, Up, Down]
+            Day02::parse("UL\nRxD").unwrap_err().to_string(),
+            ParseError::new(2, 2, "U, D, L or R", "'xD'").to_string()
         );
     }
 }
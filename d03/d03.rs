@@ -1,17 +1,37 @@
-use crate::part2::parse_vertical;
+use aoc2016::ParseError;
+use aoc2016::parse::parse_lines;
+use aoc2016::solution::{self, Solution};
+use part2::parse_vertical;
+use std::error::Error;
+use std::fmt::Display;
 
 fn triangle_is_valid([a, b, c]: [i32; 3]) -> bool {
     a + b > c && a + c > b && b + c > a
 }
 
-fn parse_horizontal(line: &str) -> Option<[i32; 3]> {
-    let mut it = line.split_whitespace().map(str::parse).map(Result::ok);
-    let res = Some([it.next()??, it.next()??, it.next()??]);
-    if it.next().is_some() { None } else { res }
+fn parse_horizontal(line: &str) -> Result<[i32; 3], ParseError> {
+    let mut rest = line;
+    let mut side = || {
+        rest = rest.trim_start();
+        let len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (side, tail) = rest.split_at(len);
+        let result = side
+            .parse()
+            .map_err(|_| ParseError::at(line, rest, "a side length"));
+        rest = tail;
+        result
+    };
+    let res = [side()?, side()?, side()?];
+    let rest = rest.trim_start();
+    if rest.is_empty() {
+        Ok(res)
+    } else {
+        Err(ParseError::at(line, rest, "end of line"))
+    }
 }
 
 mod part2 {
-    use crate::parse_horizontal;
+    use super::parse_horizontal;
 
     struct ParseVerticalTrianglesIterator<Lines> {
         lines: Lines,
@@ -33,9 +53,9 @@ mod part2 {
                 return Some(col3);
             }
 
-            let r1 = parse_horizontal(self.lines.next()?)?;
-            let r2 = parse_horizontal(self.lines.next()?)?;
-            let r3 = parse_horizontal(self.lines.next()?)?;
+            let r1 = parse_horizontal(self.lines.next()?).ok()?;
+            let r2 = parse_horizontal(self.lines.next()?).ok()?;
+            let r3 = parse_horizontal(self.lines.next()?).ok()?;
 
             self.col2 = Some([r1[1], r2[1], r3[1]]);
             self.col3 = Some([r1[2], r2[2], r3[2]]);
@@ -89,19 +109,34 @@ mod part2 {
     }
 }
 
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+    type Input<'i> = &'i str;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        parse_lines(input, parse_horizontal)?;
+        Ok(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> impl Display {
+        input
+            .lines()
+            .filter_map(|l| parse_horizontal(l).ok())
+            .filter(|t| triangle_is_valid(*t))
+            .count()
+    }
+
+    fn part2(input: &Self::Input<'_>) -> impl Display {
+        parse_vertical(input.lines())
+            .filter(|t| triangle_is_valid(*t))
+            .count()
+    }
+}
+
 fn main() {
-    let input = include_str!("d03.txt");
-    let valid_count = input
-        .lines()
-        .filter_map(parse_horizontal)
-        .filter(|t| triangle_is_valid(*t))
-        .count();
-    println!("Part1: {}", valid_count);
-
-    let valid_count = parse_vertical(input.lines())
-        .filter(|t| triangle_is_valid(*t))
-        .count();
-    println!("Part2: {}", valid_count);
+    solution::main::<Day03>();
 }
 
 #[cfg(test)]
@@ -110,7 +145,19 @@ mod tests {
 
     #[test]
     fn test_parse() {
-        assert_eq!(parse_horizontal("5 10 25"), Some([5, 10, 25]));
+        assert_eq!(parse_horizontal("  5 10 25"), Ok([5, 10, 25]));
+        assert_eq!(
+            parse_horizontal("  5 1x 25"),
+            Err(ParseError::new(1, 5, "a side length", "'1x'"))
+        );
+        assert_eq!(
+            parse_horizontal("5 10"),
+            Err(ParseError::new(1, 5, "a side length", "end of input"))
+        );
+        assert_eq!(
+            parse_horizontal("5 10 25 3"),
+            Err(ParseError::new(1, 9, "end of line", "'3'"))
+        );
     }
 
     #[test]
@@ -1,12 +1,14 @@
-#![feature(array_try_from_fn)]
-
-use aoc2016::{ALPHABET_COUNT, AlphabetMap};
+use aoc2016::parse::parse_lines;
+use aoc2016::solution::{self, Solution};
+use aoc2016::{ALPHABET_COUNT, AlphabetMap, ParseError};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::array::from_fn;
+use std::error::Error;
+use std::fmt::Display;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
-struct Room<'s> {
+pub struct Room<'s> {
     name: &'s str,
     sector_id: u32,
     checksum: [char; 5],
@@ -15,18 +17,23 @@ struct Room<'s> {
 // can't use FromStr because that doesn't allow you to return a type depending on the lifetime
 // of the &str
 impl<'s> TryFrom<&'s str> for Room<'s> {
-    type Error = ();
+    type Error = ParseError;
 
     fn try_from(s: &'s str) -> Result<Self, Self::Error> {
         lazy_static! {
             // language=regexp
             static ref Pat: Regex = Regex::new(r#"([a-z\-]*?)-(\d+)\[([a-z]{5})]"#).unwrap();
         }
-        let (_, [name, sector_id, checksum]) = Pat.captures(s).ok_or(())?.extract();
+        let (_, [name, sector_id, checksum]) = Pat
+            .captures(s)
+            .ok_or_else(|| ParseError::at(s, s, "a room like 'name-123[check]'"))?
+            .extract();
         let mut c = checksum.chars();
         Ok(Room {
             name,
-            sector_id: sector_id.parse().map_err(|_| ())?,
+            sector_id: sector_id
+                .parse()
+                .map_err(|_| ParseError::at(s, sector_id, "a sector id"))?,
             // unwrap is safe here because the regex would have failed already.
             checksum: from_fn(|_| c.next().unwrap()),
         })
@@ -37,16 +44,8 @@ fn calculate_checksum(name: &str) -> [char; 5] {
     const {
         assert!(ALPHABET_COUNT >= 5);
     }
-    let mut counts = AlphabetMap::<usize>::new();
-    for c in name.chars() {
-        if !c.is_ascii_lowercase() {
-            continue;
-        }
-        counts[c] += 1;
-    }
-    let mut entries = <[(char, usize); _]>::from(counts);
-    entries.sort_by_key(|&(c, count)| (usize::MAX - count, c));
-    let mut it = entries.into_iter().map(|(c, _)| c);
+    let counts = AlphabetMap::<usize>::histogram(name.chars());
+    let mut it = counts.by_frequency().map(|(c, _)| c);
     from_fn(|_| it.next().unwrap())
 }
 
@@ -73,22 +72,35 @@ impl Room<'_> {
     }
 }
 
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+    type Input<'i> = Vec<Room<'i>>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        Ok(parse_lines(input, Room::try_from)?)
+    }
+
+    fn part1(rooms: &Self::Input<'_>) -> impl Display {
+        rooms
+            .iter()
+            .filter(|r| r.is_valid())
+            .map(|r| r.sector_id)
+            .sum::<u32>()
+    }
+
+    fn part2(rooms: &Self::Input<'_>) -> impl Display {
+        rooms
+            .iter()
+            .filter(|r| r.is_valid())
+            .find(|r| r.decrypt_name() == "northpole object storage")
+            .map_or_else(|| "none".to_string(), |r| r.sector_id.to_string())
+    }
+}
+
 fn main() {
-    let input = include_str!("d04.txt");
-    let valid_rooms: Vec<_> = input
-        .lines()
-        .map(Room::try_from)
-        .map(Result::unwrap)
-        .filter(Room::is_valid)
-        .collect();
-    let sector_sum: u32 = valid_rooms.iter().map(|r| r.sector_id).sum();
-    println!("Part1: {}", sector_sum);
-
-    let north_pole_storage = valid_rooms
-        .into_iter()
-        .find(|r| r.decrypt_name() == "northpole object storage")
-        .unwrap();
-    println!("Part2: {}", north_pole_storage.sector_id);
+    solution::main::<Day04>();
 }
 
 #[cfg(test)]
@@ -139,6 +151,17 @@ mod tests {
             .map(|s| Room::try_from(s).unwrap().is_valid()),
             [true, true, true, false]
         );
+        assert_eq!(
+            Room::try_from("aaaaa-bbb-99999999999[abxyz]"),
+            Err(ParseError::new(1, 11, "a sector id", "'99999999999'"))
+        );
+    }
+
+    #[test]
+    fn test_no_storage() {
+        let rooms = Day04::parse("aaa-1[abcde]").unwrap();
+        assert_eq!(Day04::part1(&rooms).to_string(), "1");
+        assert_eq!(Day04::part2(&rooms).to_string(), "none");
     }
 
     #[test]
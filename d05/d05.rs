@@ -1,4 +1,8 @@
+use aoc2016::progress::Progress;
+use aoc2016::solution::{self, Solution};
 use md5::{Digest, Md5};
+use std::error::Error;
+use std::fmt::Display;
 use std::io::Write;
 
 #[inline(always)]
@@ -9,7 +13,9 @@ fn char_from_half_byte(hex: u8) -> u8 {
 fn get_password_1(input: &[u8]) -> String {
     let mut buf = Vec::new();
     let mut result = Vec::new();
+    let mut progress = Progress::new("d05 password 1");
     for i in 0u64.. {
+        progress.inc();
         // I do this instead of format! because .clear does not touch the capacity.
         // Therefore, if the resulting string is the same length as before (as is the case almost
         // all the time), there will be no allocations.
@@ -34,7 +40,9 @@ fn get_password_2(input: &[u8]) -> String {
     let mut buf = Vec::new();
     let mut char_count = 0;
     let mut result = [0u8; 8];
+    let mut progress = Progress::new("d05 password 2");
     for i in 0u64.. {
+        progress.inc();
         buf.clear();
         buf.extend_from_slice(input);
         write!(&mut buf, "{}", i).unwrap();
@@ -60,12 +68,25 @@ fn get_password_2(input: &[u8]) -> String {
     unreachable!();
 }
 
-fn main() {
-    // let input = b"abc";
-    let input = b"ffykfhsq";
-    let password = get_password_1(input);
-    println!("Part1: {}", password);
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+    type Input<'i> = &'i [u8];
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        Ok(input.trim().as_bytes())
+    }
+
+    fn part1(door_id: &Self::Input<'_>) -> impl Display {
+        get_password_1(door_id)
+    }
 
-    let password = get_password_2(input);
-    println!("Part2: {}", password);
+    fn part2(door_id: &Self::Input<'_>) -> impl Display {
+        get_password_2(door_id)
+    }
+}
+
+fn main() {
+    solution::main::<Day05>();
 }
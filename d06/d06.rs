@@ -1,4 +1,8 @@
-use aoc2016::AlphabetMap;
+use aoc2016::parse::parse_lines;
+use aoc2016::solution::{self, Solution};
+use aoc2016::{AlphabetMap, ParseError};
+use std::error::Error;
+use std::fmt::Display;
 
 enum Mode {
     MostCommon,
@@ -21,27 +25,52 @@ fn correct_error<'s>(words: impl IntoIterator<Item = &'s str>, mode: Mode) -> St
         }
 
         for (i, c) in word.chars().enumerate() {
-            letter_counts[i][c] += 1;
+            if let Some(count) = letter_counts[i].get_mut(c) {
+                *count += 1;
+            }
         }
     }
 
-    let key_fn: fn(&_) -> _ = match mode {
-        Mode::MostCommon => |&(ch, co)| (usize::MAX - co, ch),
-        Mode::LeastCommon => |&(ch, co)| (if co == 0 { usize::MAX } else { co }, ch),
-    };
-
     letter_counts
         .into_iter()
-        .map(|map| map.into_iter().min_by_key(key_fn).unwrap().0)
+        .map(|map| match mode {
+            Mode::MostCommon => map.most_common().unwrap().0,
+            Mode::LeastCommon => map.least_common_present().unwrap().0,
+        })
         .collect()
 }
 
-fn main() {
-    // let input = include_str!("d06.sample.txt");
-    let input = include_str!("d06.txt");
-    let message = correct_error(input.lines(), Mode::MostCommon);
-    println!("Part1: {}", message);
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+    type Input<'i> = &'i str;
 
-    let message = correct_error(input.lines(), Mode::LeastCommon);
-    println!("Part2: {}", message);
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        let mut len = None;
+        parse_lines(input, |l| {
+            let l = l.trim_end();
+            if let Some(i) = l.chars().position(|c| !c.is_ascii_lowercase()) {
+                return Err(ParseError::at_char(l, i, "a lowercase letter"));
+            }
+            match *len.get_or_insert(l.len()) {
+                n if n < l.len() => Err(ParseError::at_char(l, n, "end of line")),
+                n if n > l.len() => Err(ParseError::at_char(l, l.len(), "a lowercase letter")),
+                _ => Ok(()),
+            }
+        })?;
+        Ok(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> impl Display {
+        correct_error(input.lines(), Mode::MostCommon)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> impl Display {
+        correct_error(input.lines(), Mode::LeastCommon)
+    }
+}
+
+fn main() {
+    solution::main::<Day06>();
 }
@@ -1,5 +1,29 @@
+use aoc2016::ParseError;
+use aoc2016::parse::parse_lines;
+use aoc2016::solution::{self, Solution};
 use itertools::Itertools;
 use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::Display;
+
+/// Checks that `line` only consists of letters and non-nested bracketed sequences.
+fn check_address(line: &str) -> Result<(), ParseError> {
+    let line = line.trim_end();
+    let mut inside_brackets = false;
+    for (i, c) in line.chars().enumerate() {
+        match c {
+            'a'..='z' => {}
+            '[' if !inside_brackets => inside_brackets = true,
+            ']' if inside_brackets => inside_brackets = false,
+            _ if inside_brackets => return Err(ParseError::at_char(line, i, "a letter or ']'")),
+            _ => return Err(ParseError::at_char(line, i, "a letter or '['")),
+        }
+    }
+    if inside_brackets {
+        return Err(ParseError::at_char(line, line.chars().count(), "']'"));
+    }
+    Ok(())
+}
 
 fn supports_tls(ipv7_addr: &str) -> bool {
     let mut is_inside_brackets = false;
@@ -57,19 +81,47 @@ fn supports_ssl(ipv7_addr: &str) -> bool {
     abas.into_iter().any(|[a, b]| babs.contains(&[b, a]))
 }
 
-fn main() {
-    let input = include_str!("d07.txt");
-    let valid_count = input.lines().filter(|l| supports_tls(l)).count();
-    println!("Part1: {}", valid_count);
+pub struct Day07;
+
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+    type Input<'i> = &'i str;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        parse_lines(input, check_address)?;
+        Ok(input)
+    }
 
-    let valid_count = input.lines().filter(|l| supports_ssl(l)).count();
-    println!("Part2: {}", valid_count);
+    fn part1(input: &Self::Input<'_>) -> impl Display {
+        input.lines().filter(|l| supports_tls(l)).count()
+    }
+
+    fn part2(input: &Self::Input<'_>) -> impl Display {
+        input.lines().filter(|l| supports_ssl(l)).count()
+    }
+}
+
+fn main() {
+    solution::main::<Day07>();
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_check_address() {
+        assert_eq!(check_address("abba[mnop]qrst"), Ok(()));
+        assert_eq!(
+            check_address("abba[mn[op]]"),
+            Err(ParseError::new(1, 8, "a letter or ']'", "'[op]]'"))
+        );
+        assert_eq!(
+            check_address("abba[mnop"),
+            Err(ParseError::new(1, 10, "']'", "end of input"))
+        );
+    }
+
     #[test]
     fn test_supports_tls() {
         assert!(supports_tls("abba[mnop]qrst"));
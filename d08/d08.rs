@@ -1,8 +1,22 @@
+use aoc2016::image::{Gif, write_pbm};
+use aoc2016::ocr::Font;
+use aoc2016::parse::parse_lines;
+use aoc2016::solution::{self, InputSource, Solution};
+use aoc2016::{Grid, Json, ParseError};
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::fmt;
-use std::fmt::{Formatter, Write};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs::{self, File};
+use std::io::{BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
+use std::{env, fmt, io, process, thread};
+
+/// Size of the screen every instruction is checked against.
+const WIDTH: u32 = 50;
+const HEIGHT: u32 = 6;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 enum Instruction {
@@ -12,103 +26,97 @@ enum Instruction {
 }
 
 impl FromStr for Instruction {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         lazy_static! {
             // language=regexp
             static ref PAT: Regex = Regex::new(
                 r#"(?ix)
-                    rect\s+(?<w>\d+)x(?<h>\d+)| # rect 2x1
-                    rotate\s+(?:
-                        row\s+y=(?<y>\d+)|      # rotate row y=0 by 5
-                        column\s+x=(?<x>\d+)    # rotate column x=0 by 1
-                    )\s+by\s+(?<by>\d+)
+                    ^\s*(?:
+                        rect\s+(?<w>\d+)x(?<h>\d+)| # rect 2x1
+                        rotate\s+(?:
+                            row\s+y=(?<y>\d+)|      # rotate row y=0 by 5
+                            column\s+x=(?<x>\d+)    # rotate column x=0 by 1
+                        )\s+by\s+(?<by>\d+)
+                    )
                 "#
             ).unwrap();
         }
 
-        || -> Option<_> {
-            let cap = PAT.captures(s.trim())?;
-            match (
-                (cap.name("w"), cap.name("h")),
-                cap.name("y"),
-                cap.name("x"),
-                cap.name("by"),
-            ) {
-                ((Some(w), Some(h)), ..) => Some(Self::Rect(
-                    w.as_str().parse().ok()?,
-                    h.as_str().parse().ok()?,
-                )),
-                (_, Some(y), _, Some(by)) => Some(Self::RotateRow(
-                    y.as_str().parse().ok()?,
-                    by.as_str().parse().ok()?,
-                )),
-                (_, _, Some(x), Some(by)) => Some(Self::RotateCol(
-                    x.as_str().parse().ok()?,
-                    by.as_str().parse().ok()?,
-                )),
-                _ => None,
+        let cap = PAT.captures(s).ok_or_else(|| {
+            ParseError::at(s, s.trim_start(), "'rect AxB' or 'rotate row|column'")
+        })?;
+        let rest = s[cap.get(0).unwrap().end()..].trim_start();
+        if !rest.is_empty() {
+            return Err(ParseError::at(s, rest, "end of line"));
+        }
+        let num = |name: &str| -> Result<u32, ParseError> {
+            let m = cap.name(name).unwrap();
+            m.as_str()
+                .parse()
+                .map_err(|_| ParseError::at(s, &s[m.range()], "a number"))
+        };
+        // positions and sizes have to fit on the screen
+        let bounded = |name: &str, max: u32, what: &str| -> Result<u32, ParseError> {
+            let n = num(name)?;
+            if n > max {
+                let m = cap.name(name).unwrap();
+                return Err(ParseError::at(
+                    s,
+                    &s[m.range()],
+                    format!("{what} of at most {max}"),
+                ));
             }
-        }()
-        .ok_or(())
+            Ok(n)
+        };
+        if cap.name("w").is_some() {
+            Ok(Self::Rect(
+                bounded("w", WIDTH, "a width")?,
+                bounded("h", HEIGHT, "a height")?,
+            ))
+        } else if cap.name("y").is_some() {
+            Ok(Self::RotateRow(
+                bounded("y", HEIGHT - 1, "a row")?,
+                num("by")?,
+            ))
+        } else {
+            Ok(Self::RotateCol(
+                bounded("x", WIDTH - 1, "a column")?,
+                num("by")?,
+            ))
+        }
     }
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
-struct Screen {
-    width: u32,
-    height: u32,
-    values: Vec<bool>,
+pub struct Screen {
+    pixels: Grid<bool>,
 }
 
 impl Screen {
     fn new(width: u32, height: u32) -> Self {
         Self {
-            width,
-            height,
-            values: vec![false; (width * height) as usize],
+            pixels: Grid::new(width as usize, height as usize, false),
         }
     }
 
-    fn idx(&self, x: u32, y: u32) -> usize {
-        y as usize * self.width as usize + x as usize
-    }
-
     fn rect(mut self, width: u32, height: u32) -> Self {
         for row in 0..height {
             for col in 0..width {
-                let idx = self.idx(col, row);
-                self.values[idx] = true;
+                self.pixels[[col, row]] = true;
             }
         }
         self
     }
 
     fn rot_row(mut self, y: u32, by: u32) -> Self {
-        let by = match by {
-            0 => return self,
-            1.. => by % self.width,
-        };
-        let span = self.idx(0, y)..self.idx(0, y + 1);
-        self.values[span].rotate_right(by as usize);
+        self.pixels.rotate_row_right(y as usize, by as usize);
         self
     }
 
     fn rot_col(mut self, x: u32, by: u32) -> Self {
-        let by = match by {
-            0 => return self,
-            1.. => by % self.height,
-        };
-        let mut col: Vec<_> = (0..self.height)
-            .map(|y| self.idx(x, y))
-            .map(|i| self.values[i])
-            .collect();
-        col.rotate_right(by as usize);
-        for (y, v) in col.into_iter().enumerate() {
-            let i = self.idx(x, y as u32);
-            self.values[i] = v;
-        }
+        self.pixels.rotate_column_down(x as usize, by as usize);
         self
     }
 
@@ -123,30 +131,212 @@ impl Screen {
 
 impl fmt::Display for Screen {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let mut start_nl = false;
-        for l in self.values.chunks(self.width as usize) {
-            if start_nl {
-                writeln!(f)?;
-            } else {
-                start_nl = true;
+        self.pixels
+            .display_with(|&b| if b { '#' } else { '.' })
+            .fmt(f)
+    }
+}
+
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: u8 = 8;
+    type Input<'i> = Screen;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        let instructions = parse_lines(input, str::parse)?;
+        Ok(instructions
+            .into_iter()
+            .fold(Screen::new(WIDTH, HEIGHT), Screen::execute))
+    }
+
+    fn part1(screen: &Self::Input<'_>) -> impl Display {
+        screen.pixels.cells().iter().filter(|e| **e).count()
+    }
+
+    fn part2(screen: &Self::Input<'_>) -> impl Display {
+        match Font::aoc_4x6().read(&screen.pixels) {
+            Ok(text) => text,
+            Err(e) => format!("{e}\n{screen}"),
+        }
+    }
+
+    fn debug(screen: &Self::Input<'_>) -> Option<Json> {
+        Some(screen.to_string().into())
+    }
+}
+
+/// The screen before any instruction and after each of them.
+fn frames(instructions: &[Instruction]) -> Vec<Screen> {
+    let mut screen = Screen::new(WIDTH, HEIGHT);
+    let mut frames = vec![screen.clone()];
+    for &instruction in instructions {
+        screen = screen.execute(instruction);
+        frames.push(screen.clone());
+    }
+    frames
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct AnimateOptions {
+    input: InputSource,
+    fps: f64,
+    gif: Option<PathBuf>,
+    pbm: Option<PathBuf>,
+    scale: usize,
+}
+
+impl AnimateOptions {
+    /// Parses `[--fps N] [--gif FILE] [--pbm DIR] [--scale N]` and whatever
+    /// [`InputSource::from_args`] takes.
+    fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self {
+            input: InputSource::Input,
+            fps: 10.0,
+            gif: None,
+            pbm: None,
+            scale: 8,
+        };
+        let mut rest = vec![];
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+            match arg.as_str() {
+                "--fps" => {
+                    options.fps = value()?
+                        .parse()
+                        .ok()
+                        .filter(|&fps: &f64| fps > 0.0 && fps.is_finite())
+                        .ok_or("--fps needs a positive number")?
+                }
+                "--gif" => options.gif = Some(value()?.into()),
+                "--pbm" => options.pbm = Some(value()?.into()),
+                "--scale" => {
+                    options.scale = value()?
+                        .parse()
+                        .ok()
+                        .filter(|&scale| scale > 0)
+                        .ok_or("--scale needs a positive number")?
+                }
+                _ => rest.push(arg),
             }
-            l.iter()
-                .map(|&b| if b { '#' } else { '.' })
-                .try_for_each(|c| f.write_char(c))?;
         }
-        Ok(())
+        options.input = InputSource::from_args(rest)?;
+        Ok(options)
+    }
+
+    fn frame_time(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.fps)
+    }
+}
+
+/// Shows the cursor again once dropped, so it also comes back if playing stops half way.
+struct ShowCursor;
+
+impl Drop for ShowCursor {
+    fn drop(&mut self) {
+        let mut out = io::stdout();
+        // there is nothing left to report a failure to
+        let _ = write!(out, "\x1b[?25h").and_then(|_| out.flush());
+    }
+}
+
+/// Draws the frames over each other, moving the cursor back up to the top of the screen
+/// between them.
+fn play(frames: &[Screen], frame_time: Duration) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    let terminal = io::stdout().is_terminal();
+    let _show_cursor = if terminal {
+        write!(out, "\x1b[?25l")?;
+        Some(ShowCursor)
+    } else {
+        None
+    };
+    draw(&mut out, frames, terminal.then_some(frame_time))
+}
+
+/// Writes every frame followed by a newline. With a `frame_time`, each frame waits that long and
+/// is then overwritten by the next one. Without, the frames are separated by empty lines.
+fn draw(out: &mut impl Write, frames: &[Screen], frame_time: Option<Duration>) -> io::Result<()> {
+    for (i, frame) in frames.iter().enumerate() {
+        if i > 0 {
+            match frame_time {
+                // back to the start of the frame's first line, from the line below its last
+                Some(_) => write!(out, "\x1b[{}A\r", frame.pixels.height())?,
+                None => writeln!(out)?,
+            }
+        }
+        writeln!(
+            out,
+            "{}",
+            frame.pixels.display_with(|&b| if b { '█' } else { ' ' })
+        )?;
+        out.flush()?;
+        if let Some(frame_time) = frame_time {
+            thread::sleep(frame_time);
+        }
     }
+    Ok(())
+}
+
+fn write_gif(path: &Path, frames: &[Screen], options: &AnimateOptions) -> io::Result<()> {
+    let [width, height] = [frames[0].pixels.width(), frames[0].pixels.height()];
+    let file = BufWriter::new(File::create(path)?);
+    let mut gif = Gif::new(file, width, height, options.scale)?;
+    for (i, frame) in frames.iter().enumerate() {
+        // stay on the finished screen for a while before starting over
+        let delay = if i + 1 == frames.len() {
+            Duration::from_secs(3)
+        } else {
+            options.frame_time()
+        };
+        gif.frame(&frame.pixels, delay)?;
+    }
+    gif.finish()?;
+    Ok(())
+}
+
+fn write_pbms(dir: &Path, frames: &[Screen], scale: usize) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    for (i, frame) in frames.iter().enumerate() {
+        let file = BufWriter::new(File::create(dir.join(format!("frame{i:04}.pbm")))?);
+        write_pbm(file, &frame.pixels, scale)?;
+    }
+    Ok(())
+}
+
+/// `d08 animate`: plays the instructions on the terminal, or writes them as images with
+/// `--gif` and `--pbm`.
+fn animate(args: impl IntoIterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let options = AnimateOptions::from_args(args).unwrap_or_else(|e| {
+        eprintln!(
+            "{e}\nusage: d08 animate [--fps N] [--gif FILE] [--pbm DIR] [--scale N] [--sample] [FILE|-]"
+        );
+        process::exit(2);
+    });
+    let input = options.input.read(Day08::DAY)?;
+    let frames = frames(&parse_lines(&input, str::parse)?);
+    if let Some(path) = &options.gif {
+        write_gif(path, &frames, &options)?;
+    }
+    if let Some(dir) = &options.pbm {
+        write_pbms(dir, &frames, options.scale)?;
+    }
+    if options.gif.is_none() && options.pbm.is_none() {
+        play(&frames, options.frame_time())?;
+    }
+    Ok(())
 }
 
 fn main() {
-    let input = include_str!("d08.txt");
-    let result = input
-        .lines()
-        .map(|s| s.parse().unwrap())
-        .fold(Screen::new(50, 6), Screen::execute);
-    let lit_count = result.values.iter().cloned().filter(|e| *e).count();
-    println!("Lit count: {}", lit_count);
-    println!("{}", result);
+    let mut args = env::args().skip(1).peekable();
+    if args.next_if(|arg| arg == "animate").is_none() {
+        return solution::main::<Day08>();
+    }
+    if let Err(e) = animate(args) {
+        eprintln!("{e}");
+        process::exit(1);
+    }
 }
 
 #[cfg(test)]
@@ -161,20 +351,59 @@ mod tests {
         assert_eq!("rotate column x=17 by 1".parse(), Ok(RotateCol(17, 1)));
     }
 
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "xx rect 3x2".parse::<Instruction>(),
+            Err(ParseError::new(
+                1,
+                1,
+                "'rect AxB' or 'rotate row|column'",
+                "'xx'"
+            ))
+        );
+        assert_eq!(
+            "rect 3x2 garbage".parse::<Instruction>(),
+            Err(ParseError::new(1, 10, "end of line", "'garbage'"))
+        );
+        assert_eq!(
+            "rotate row y=1 by 9 by 2".parse::<Instruction>(),
+            Err(ParseError::new(1, 21, "end of line", "'by'"))
+        );
+        assert_eq!(
+            "  rotate diagonal x=1 by 1".parse::<Instruction>(),
+            Err(ParseError::new(
+                1,
+                3,
+                "'rect AxB' or 'rotate row|column'",
+                "'rotate'"
+            ))
+        );
+        assert_eq!(
+            "rect 99999999999x1".parse::<Instruction>(),
+            Err(ParseError::new(1, 6, "a number", "'99999999999'"))
+        );
+        assert_eq!(
+            "rect 51x1".parse::<Instruction>(),
+            Err(ParseError::new(1, 6, "a width of at most 50", "'51'"))
+        );
+        assert_eq!(
+            "rect 50x7".parse::<Instruction>(),
+            Err(ParseError::new(1, 9, "a height of at most 6", "'7'"))
+        );
+        assert_eq!(
+            "rotate row y=9 by 1".parse::<Instruction>(),
+            Err(ParseError::new(1, 14, "a row of at most 5", "'9'"))
+        );
+        assert_eq!(
+            "rotate column x=60 by 1".parse::<Instruction>(),
+            Err(ParseError::new(1, 17, "a column of at most 49", "'60'"))
+        );
+    }
+
     fn screen(s: &str) -> Screen {
-        let mut width = 0u32;
-        let values: Vec<_> = s
-            .lines()
-            .map(str::trim)
-            .filter(|l| !l.is_empty())
-            .inspect(|l| width = l.len() as u32)
-            .flat_map(str::chars)
-            .map(|c| c == '#')
-            .collect();
         Screen {
-            width,
-            height: values.len() as u32 / width,
-            values,
+            pixels: Grid::parse_with(s, |_, c| Some(c == '#')).unwrap(),
         }
     }
 
@@ -183,11 +412,13 @@ mod tests {
         assert_eq!(
             screen("##..\n##..\n....\n"),
             Screen {
-                width: 4,
-                height: 3,
-                values: vec![
-                    true, true, false, false, true, true, false, false, false, false, false, false
-                ],
+                pixels: Grid::from_vec(
+                    4,
+                    vec![
+                        true, true, false, false, true, true, false, false, false, false, false,
+                        false
+                    ]
+                ),
             }
         );
     }
@@ -253,4 +484,38 @@ mod tests {
             screen(".#..#.#\n#.#....\n.#.....\n")
         );
     }
+
+    #[test]
+    fn test_frames() {
+        let instructions = ["rect 3x2", "rotate column x=1 by 1", "rotate row y=0 by 4"]
+            .map(|s| s.parse().unwrap());
+        let frames = frames(&instructions);
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0], Screen::new(WIDTH, HEIGHT));
+        assert_eq!(frames[1].pixels.cells().iter().filter(|&&p| p).count(), 6);
+        assert_eq!(frames[3], frames[2].clone().rot_row(0, 4));
+    }
+
+    #[test]
+    fn test_draw() {
+        let frames = [Screen::new(2, 2), Screen::new(2, 2).rect(1, 1)];
+        let mut out = Vec::new();
+        draw(&mut out, &frames, None).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "  \n  \n\n█ \n  \n");
+        let mut out = Vec::new();
+        draw(&mut out, &frames, Some(Duration::ZERO)).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "  \n  \n\x1b[2A\r█ \n  \n");
+    }
+
+    #[test]
+    fn test_animate_options() {
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+        let options = AnimateOptions::from_args(args("--fps 4 --gif out.gif --sample")).unwrap();
+        assert_eq!(options.fps, 4.0);
+        assert_eq!(options.frame_time(), Duration::from_millis(250));
+        assert_eq!(options.gif, Some("out.gif".into()));
+        assert_eq!(options.input, InputSource::Sample);
+        assert!(AnimateOptions::from_args(args("--fps 0")).is_err());
+        assert!(AnimateOptions::from_args(args("--scale")).is_err());
+    }
 }
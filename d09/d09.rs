@@ -1,5 +1,11 @@
-// this time around, I'm using panics instead of results, because it's a one-off script.
-// results are useful for web-servers or other programs that are expected to resume computation.
+// markers are checked once while parsing, for both versions, so the parts can't run into
+// malformed ones. everything works on bytes, so only ASCII text is accepted.
+
+use aoc2016::ParseError;
+use aoc2016::solution::{self, Solution};
+use std::error::Error;
+use std::fmt::Display;
+use std::io::{self, Read};
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
 struct Marker {
@@ -7,101 +13,208 @@ struct Marker {
     rep: usize,
 }
 
-/// `it` is expected to have already consumed the opening parenthesis.
-/// This avoids the caller having to look ahead before consuming characters.
-fn parse_marker(it: impl Iterator<Item = u8>) -> Marker {
-    enum State {
-        ParsingLen,
-        ParsingRep,
-    }
-    let mut state = State::ParsingLen;
-    let mut out = Marker::default();
-    for c in it {
-        match state {
-            State::ParsingLen => match c {
-                b'0'..=b'9' => {
-                    out.len = out.len * 10 + (c - b'0') as usize;
-                }
-                b'x' => {
-                    state = State::ParsingRep;
-                }
-                _ => panic!("expected [0-9x], got '{c}'"),
-            },
-            State::ParsingRep => match c {
-                b'0'..=b'9' => {
-                    out.rep = out.rep * 10 + (c - b'0') as usize;
-                }
-                b')' => return out,
-                _ => panic!("expected [0-9)], got '{c}'"),
-            },
+/// Parses the `(AxB)` marker at byte `start` of `text`. Returns it together with the offset of the
+/// text right after it.
+fn parse_marker(text: &str, start: usize) -> Result<(Marker, usize), ParseError> {
+    fn number<'s>(text: &str, rest: &'s str) -> Result<(usize, &'s str), ParseError> {
+        let len = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        match rest[..len].parse() {
+            Ok(n) => Ok((n, &rest[len..])),
+            Err(_) => Err(ParseError::at(text, rest, "a number")),
+        }
+    }
+    fn expect<'s>(text: &str, rest: &'s str, c: char) -> Result<&'s str, ParseError> {
+        rest.strip_prefix(c)
+            .ok_or_else(|| ParseError::at(text, rest, format!("'{c}'")))
+    }
+    let rest = expect(text, &text[start..], '(')?;
+    let (len, rest) = number(text, rest)?;
+    let rest = expect(text, rest, 'x')?;
+    let (rep, rest) = number(text, rest)?;
+    let rest = expect(text, rest, ')')?;
+    Ok((Marker { len, rep }, text.len() - rest.len()))
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Version {
+    /// Markers only repeat the text after them.
+    V1,
+    /// Markers within repeated text are expanded as well.
+    V2,
+}
+
+/// A repeated section of the compressed text which is being expanded.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+struct Section {
+    start: usize,
+    end: usize,
+    pos: usize,
+    /// Including the one in progress.
+    reps: usize,
+}
+
+impl Section {
+    fn whole(text: &str) -> Self {
+        Self {
+            start: 0,
+            end: text.len(),
+            pos: 0,
+            reps: 1,
+        }
+    }
+
+    /// The section repeated by the marker at `self.pos`, which is moved past it. The repeated text
+    /// has to be within this section.
+    fn expand(&mut self, text: &str) -> Result<Section, ParseError> {
+        let (marker, start) = parse_marker(text, self.pos)?;
+        let end = start + marker.len;
+        if end > self.end {
+            let left = &text[start..self.end];
+            return Err(ParseError::at(
+                text,
+                left,
+                format!("{} characters to repeat", marker.len),
+            ));
+        }
+        self.pos = end;
+        Ok(Section {
+            start,
+            end,
+            pos: start,
+            reps: marker.rep,
+        })
+    }
+}
+
+/// Yields the decompressed text one byte at a time, without ever holding more of it than the
+/// byte being yielded. Only one [`Section`] per level of nested markers is kept.
+///
+/// A malformed marker is yielded as an error, after which there is nothing left. The text has to
+/// be ASCII, as the markers' lengths are counted in bytes.
+struct Decompressor<'i> {
+    text: &'i str,
+    version: Version,
+    sections: Vec<Section>,
+}
+
+impl<'i> Decompressor<'i> {
+    fn new(text: &'i str, version: Version) -> Self {
+        Self {
+            text,
+            version,
+            sections: vec![Section::whole(text)],
         }
     }
-    panic!("missing input");
 }
 
-fn decompress<'i>(chars: impl IntoIterator<Item = &'i u8>) -> Vec<u8> {
-    enum State {
-        Normal,
-        ParsingPat,
-    }
-    let mut state = State::Normal;
-    let mut it = chars.into_iter().copied();
-    let mut out = Vec::new();
-    let mut marker = Marker::default();
-    let mut pat = Vec::new();
-    while let Some(c) = it.next() {
-        match state {
-            State::Normal => {
-                if c == b'(' {
-                    marker = parse_marker(&mut it);
-                    pat.clear();
-                    state = State::ParsingPat;
+impl Iterator for Decompressor<'_> {
+    type Item = Result<u8, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let depth = self.sections.len();
+            let section = self.sections.last_mut()?;
+            if section.pos == section.end {
+                section.reps -= 1;
+                if section.reps == 0 {
+                    self.sections.pop();
                 } else {
-                    out.push(c);
+                    section.pos = section.start;
                 }
+                continue;
             }
-            State::ParsingPat => {
-                pat.push(c);
-                marker.len -= 1;
-                if marker.len == 0 {
-                    for _ in 0..marker.rep {
-                        out.extend(&pat);
+            let c = self.text.as_bytes()[section.pos];
+            if c == b'(' && (self.version == Version::V2 || depth == 1) {
+                let inner = match section.expand(self.text) {
+                    Ok(inner) => inner,
+                    Err(e) => {
+                        self.sections.clear();
+                        return Some(Err(e));
                     }
-                    state = State::Normal;
+                };
+                if inner.reps > 0 && inner.start < inner.end {
+                    self.sections.push(inner);
                 }
+                continue;
             }
+            section.pos += 1;
+            return Some(Ok(c));
         }
     }
-    out
 }
 
-fn decompressed_length<'i>(chars: impl IntoIterator<Item = &'i u8>) -> usize {
-    // at the very least, the outermost recursion layer doesn't use vtables.
-    // recursion depth is limited by the pattern nesting.
-    fn inner<It: Iterator<Item = u8>>(mut it: It) -> usize {
-        let mut total_length = 0usize;
-        while let Some(c) = it.next() {
-            if c == b'(' {
-                let marker = parse_marker(&mut it);
-                total_length += marker.rep
-                    * inner::<Box<dyn Iterator<Item = u8>>>(Box::new(it.by_ref().take(marker.len)));
-            } else {
-                total_length += 1;
-            }
+/// So the decompressed text can be copied into a file or a hasher with [`io::copy`]. Malformed
+/// markers are [`io::ErrorKind::InvalidData`].
+impl Read for Decompressor<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+        // `buf` goes first, so that no byte is taken out of `self` without a place for it
+        for (slot, c) in buf.iter_mut().zip(Iterator::by_ref(self)) {
+            *slot = c.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            n += 1;
         }
-        total_length
+        Ok(n)
     }
-    inner(chars.into_iter().copied())
 }
 
-fn main() {
-    let input = include_str!("d09.txt");
+/// What [`Decompressor`] would count, with the same [`Section`]s. Instead of repeating a section,
+/// it is walked once, and every byte in it counts for all repetitions of it and the sections
+/// around it.
+fn decompressed_len(text: &str, version: Version) -> Result<usize, ParseError> {
+    let mut len = 0;
+    // `reps` is the product of the repetitions of the section and the ones around it
+    let mut sections = vec![Section::whole(text)];
+    loop {
+        let depth = sections.len();
+        let Some(section) = sections.last_mut() else {
+            return Ok(len);
+        };
+        if section.pos == section.end {
+            sections.pop();
+        } else if text.as_bytes()[section.pos] == b'(' && (version == Version::V2 || depth == 1) {
+            let mut inner = section.expand(text)?;
+            inner.reps *= section.reps;
+            sections.push(inner);
+        } else {
+            section.pos += 1;
+            len += section.reps;
+        }
+    }
+}
 
-    let decompressed = decompress(input.as_bytes());
-    println!("Part1: {}", decompressed.len());
+pub struct Day09;
 
-    let len = decompressed_length(input.as_bytes());
-    println!("Part2: {}", len);
+impl Solution for Day09 {
+    const DAY: u8 = 9;
+    type Input<'i> = &'i str;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        let input = input.trim_end();
+        if let Some(i) = input.chars().position(|c| !c.is_ascii()) {
+            return Err(ParseError::at_char(input, i, "an ASCII character").into());
+        }
+        decompressed_len(input, Version::V1)?;
+        decompressed_len(input, Version::V2)?;
+        Ok(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> impl Display {
+        // the markers have been checked while parsing
+        Decompressor::new(input, Version::V1)
+            .map_while(Result::ok)
+            .count()
+    }
+
+    fn part2(input: &Self::Input<'_>) -> impl Display {
+        // the markers have been checked while parsing
+        decompressed_len(input, Version::V2).unwrap()
+    }
+}
+
+fn main() {
+    solution::main::<Day09>();
 }
 
 #[cfg(test)]
@@ -109,32 +222,153 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_decompress() {
-        assert_eq!(decompress(b"ADVENT"), b"ADVENT");
-        assert_eq!(decompress(b"A(1x5)BC"), b"ABBBBBC");
-        assert_eq!(decompress(b"(3x3)XYZ"), b"XYZXYZXYZ");
-        assert_eq!(decompress(b"A(2x2)BCD(2x2)EFG"), b"ABCBCDEFEFG");
-        assert_eq!(decompress(b"(6x1)(1x3)A"), b"(1x3)A");
-        assert_eq!(decompress(b"X(8x2)(3x3)ABCY"), b"X(3x3)ABC(3x3)ABCY");
+    fn test_parse_marker() {
+        assert_eq!(
+            parse_marker("A(12x3)B", 1),
+            Ok((Marker { len: 12, rep: 3 }, 7))
+        );
+        assert_eq!(
+            parse_marker("A(2x2)BC\n(2,2)EFG", 9),
+            Err(ParseError::new(2, 3, "'x'", "',2)EFG'"))
+        );
+        assert_eq!(
+            parse_marker("A(2x)", 1),
+            Err(ParseError::new(1, 5, "a number", "')'"))
+        );
+        assert_eq!(
+            parse_marker("(3x3", 0),
+            Err(ParseError::new(1, 5, "')'", "end of input"))
+        );
     }
 
     #[test]
-    fn test_decompressed_length() {
-        assert_eq!(decompressed_length(b""), 0);
-        assert_eq!(decompressed_length(b"asd"), 3);
-        assert_eq!(decompressed_length(b"(1x3)asd"), 5);
-        assert_eq!(decompressed_length(b"(3x3)XYZ"), b"XYZXYZXYZ".len());
+    fn test_parse_errors() {
+        assert_eq!(
+            Day09::parse("(6x1)(2x1)\u{e9}X").unwrap_err().to_string(),
+            ParseError::new(1, 11, "an ASCII character", "'\u{e9}X'").to_string()
+        );
+        // fine for version 1, but version 2 reads the marker that is only repeated by version 1
+        assert_eq!(
+            Day09::parse("(3x1)(ab").unwrap_err().to_string(),
+            ParseError::new(1, 7, "a number", "'ab'").to_string()
+        );
+    }
+
+    fn decompress(text: &str, version: Version) -> Result<Vec<u8>, ParseError> {
+        Decompressor::new(text, version).collect()
+    }
+
+    #[test]
+    fn test_decompress() {
+        use Version::*;
+        assert_eq!(decompress("ADVENT", V1).unwrap(), b"ADVENT");
+        assert_eq!(decompress("A(1x5)BC", V1).unwrap(), b"ABBBBBC");
+        assert_eq!(decompress("(3x3)XYZ", V1).unwrap(), b"XYZXYZXYZ");
+        assert_eq!(decompress("A(2x2)BCD(2x2)EFG", V1).unwrap(), b"ABCBCDEFEFG");
+        assert_eq!(decompress("(6x1)(1x3)A", V1).unwrap(), b"(1x3)A");
+        assert_eq!(
+            decompress("X(8x2)(3x3)ABCY", V1).unwrap(),
+            b"X(3x3)ABC(3x3)ABCY"
+        );
+        assert_eq!(
+            decompress("X(8x2)(3x3)ABCY", V2).unwrap(),
+            b"XABCABCABCABCABCABCY"
+        );
+        assert_eq!(decompress("(6x0)ABCDEFG(0x5)H", V2).unwrap(), b"GH");
+        // the second `(` is repeated by the first marker
+        assert_eq!(decompress("(3x1)(ab", V1).unwrap(), b"(ab");
+        assert_eq!(
+            decompress("A(5x2)BC", V1),
+            Err(ParseError::new(1, 7, "5 characters to repeat", "'BC'"))
+        );
+        assert_eq!(
+            decompress("(3x1)(ab", V2),
+            Err(ParseError::new(1, 7, "a number", "'ab'"))
+        );
+        // the inner marker reaches past the text the outer one repeats
         assert_eq!(
-            decompressed_length(b"X(8x2)(3x3)ABCY"),
-            b"XABCABCABCABCABCABCY".len()
+            decompress("(7x2)(3x2)ABCD", V2),
+            Err(ParseError::new(1, 11, "3 characters to repeat", "'AB'"))
         );
+        let mut it = Decompressor::new("AB(2y2)CD", V1);
+        assert_eq!(it.next(), Some(Ok(b'A')));
+        assert_eq!(it.next(), Some(Ok(b'B')));
+        assert!(it.next().unwrap().is_err());
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_read() {
+        let text = "(27x12)(20x12)(13x14)(7x10)(1x12)A";
+        let mut hasher = ByteSum::default();
         assert_eq!(
-            decompressed_length(b"(27x12)(20x12)(13x14)(7x10)(1x12)A"),
+            io::copy(&mut Decompressor::new(text, Version::V2), &mut hasher).unwrap(),
             241920
         );
+        assert_eq!(hasher.0, b'A' as u64 * 241920);
+
+        let mut read = Decompressor::new("A(2x2)BCD(2x2)EFG", Version::V1);
+        let mut buf = [0; 4];
+        assert_eq!(read.read(&mut buf).unwrap(), 4);
+        assert_eq!(&buf, b"ABCB");
+        let mut rest = String::new();
+        read.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "CDEFEFG");
+
+        let mut read = Decompressor::new("A(2x2)BCD(2x2", Version::V1);
+        let error = io::copy(&mut read, &mut io::sink()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    /// Sums up everything written to it, to check that nothing gets lost on the way.
+    #[derive(Default)]
+    struct ByteSum(u64);
+
+    impl io::Write for ByteSum {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 += buf.iter().map(|&b| b as u64).sum::<u64>();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_decompressed_len() {
+        use Version::*;
+        assert_eq!(decompressed_len("", V2), Ok(0));
+        assert_eq!(decompressed_len("asd", V2), Ok(3));
+        assert_eq!(decompressed_len("(1x3)asd", V2), Ok(5));
+        assert_eq!(decompressed_len("X(8x2)(3x3)ABCY", V1), Ok(18));
+        assert_eq!(decompressed_len("X(8x2)(3x3)ABCY", V2), Ok(20));
+        assert_eq!(
+            decompressed_len("(27x12)(20x12)(13x14)(7x10)(1x12)A", V2),
+            Ok(241920)
+        );
+        assert_eq!(
+            decompressed_len(
+                "(25x3)(3x3)ABC(2x3)XY(5x2)PQRSTX(18x9)(3x2)TWO(5x7)SEVEN",
+                V2
+            ),
+            Ok(445)
+        );
+        assert_eq!(decompressed_len("(3x1)(ab", V1), Ok(3));
         assert_eq!(
-            decompressed_length(b"(25x3)(3x3)ABC(2x3)XY(5x2)PQRSTX(18x9)(3x2)TWO(5x7)SEVEN"),
-            445
+            decompressed_len("(3x1)(ab", V2),
+            Err(ParseError::new(1, 7, "a number", "'ab'"))
         );
+        for text in [
+            "(25x3)(3x3)ABC(2x3)XY(5x2)PQRSTX(18x9)(3x2)TWO(5x7)SEVEN",
+            "(6x0)ABCDEFG(0x5)H(8x2)(3x3)ABCY",
+        ] {
+            for version in [V1, V2] {
+                assert_eq!(
+                    Ok(Decompressor::new(text, version).count()),
+                    decompressed_len(text, version)
+                );
+            }
+        }
     }
 }
@@ -1,14 +1,31 @@
-use aoc2016::IndexMap;
-use std::cmp;
+use aoc2016::solution::{self, Solution};
+use aoc2016::{IndexMap, index_key};
 use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::Display;
+use std::{cmp, fmt};
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+struct BotId(u32);
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+struct OutputId(u32);
+
+index_key!(BotId);
+index_key!(OutputId);
+
+impl fmt::Display for BotId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
 
-type Id = u32;
 type Value = u32;
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 enum OutputReference {
-    Bot(Id),
-    Output(Id),
+    Bot(BotId),
+    Output(OutputId),
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
@@ -32,136 +49,288 @@ struct Input {
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 struct Bot {
-    id: Id,
+    id: BotId,
     low_output: Output,
     high_output: Output,
     tmp_value: Option<Value>,
 }
 
 mod parse {
-    use super::{Bot, Id, Input, Output, OutputReference};
-    use aoc2016::IndexMap;
+    use super::{Bot, BotId, Factory, Input, Output, OutputId, OutputReference};
+    use aoc2016::parse::{IResult, parse_all, parse_lines};
+    use aoc2016::{IndexMap, ParseError};
+    use nom::Parser;
     use nom::branch::alt;
     use nom::bytes::complete::tag;
     use nom::character::complete::u32;
-    use nom::combinator::map;
-    use nom::{IResult, Parser};
+    use nom::combinator::{consumed, map};
+    use nom::error::context;
 
-    type NomError<S> = nom::Err<nom::error::Error<S>>;
+    /// A parsed line, with the text of the bot references in it so that undefined bots can be
+    /// reported where they are used.
+    enum ParseLineResult<'i> {
+        Input(Input, &'i str),
+        Bot(Bot, [&'i str; 2]),
+    }
 
-    enum ParseLineResult {
-        Input(Input),
-        Bot(Bot),
+    impl<'i> ParseLineResult<'i> {
+        fn references(&self) -> Vec<(OutputReference, &'i str)> {
+            match self {
+                ParseLineResult::Input(input, text) => {
+                    let (Output::Unknown(r) | Output::Value(r, _)) = input.output;
+                    vec![(r, text)]
+                }
+                ParseLineResult::Bot(bot, [low, high]) => {
+                    let (Output::Unknown(l) | Output::Value(l, _)) = bot.low_output;
+                    let (Output::Unknown(h) | Output::Value(h, _)) = bot.high_output;
+                    vec![(l, low), (h, high)]
+                }
+            }
+        }
     }
 
-    fn parse_line(line: &str) -> IResult<&str, ParseLineResult> {
-        fn output_reference(s: &str) -> IResult<&str, OutputReference> {
-            alt((
-                map((tag("bot "), u32), |(_, id)| OutputReference::Bot(id)),
-                map((tag("output "), u32), |(_, id)| OutputReference::Output(id)),
-            ))
+    fn parse_line(line: &str) -> IResult<'_, ParseLineResult<'_>> {
+        fn output_reference(s: &str) -> IResult<'_, OutputReference> {
+            context(
+                "'bot N' or 'output N'",
+                alt((
+                    map((tag("bot "), u32), |(_, id)| {
+                        OutputReference::Bot(BotId(id))
+                    }),
+                    map((tag("output "), u32), |(_, id)| {
+                        OutputReference::Output(OutputId(id))
+                    }),
+                )),
+            )
             .parse(s)
         }
 
+        const START: &str = "'value' or 'bot'";
         alt((
             // value 2 goes to bot 156
             map(
-                (tag("value "), u32, tag(" goes to "), output_reference),
-                |(_, value, _, output)| {
-                    ParseLineResult::Input(Input {
-                        output: Output::Value(output, value),
-                    })
+                (
+                    context(START, tag("value ")),
+                    u32,
+                    tag(" goes to "),
+                    consumed(output_reference),
+                ),
+                |(_, value, _, (text, output))| {
+                    ParseLineResult::Input(
+                        Input {
+                            output: Output::Value(output, value),
+                        },
+                        text,
+                    )
                 },
             ),
             // bot 84 gives low to bot 174 and high to bot 155
             map(
                 (
-                    tag("bot "),
+                    context(START, tag("bot ")),
                     u32,
                     tag(" gives low to "),
-                    output_reference,
+                    consumed(output_reference),
                     tag(" and high to "),
-                    output_reference,
+                    consumed(output_reference),
                 ),
-                |(_, id, _, low, _, high)| {
-                    ParseLineResult::Bot(Bot {
-                        id,
-                        low_output: Output::Unknown(low),
-                        high_output: Output::Unknown(high),
-                        tmp_value: None,
-                    })
+                |(_, id, _, (low_text, low), _, (high_text, high))| {
+                    ParseLineResult::Bot(
+                        Bot {
+                            id: BotId(id),
+                            low_output: Output::Unknown(low),
+                            high_output: Output::Unknown(high),
+                            tmp_value: None,
+                        },
+                        [low_text, high_text],
+                    )
                 },
             ),
         ))
         .parse(line)
     }
 
-    #[allow(clippy::type_complexity)]
-    pub fn from_lines<'s>(
-        lines: impl IntoIterator<Item = &'s str>,
-    ) -> Result<(Vec<Input>, IndexMap<Id, Bot>), NomError<&'s str>> {
+    pub fn from_lines(input: &str) -> Result<Factory, ParseError> {
         let mut inputs = Vec::new();
         let mut bots = IndexMap::new();
+        let mut bot_lines = IndexMap::new();
 
-        for line in lines {
-            match parse_line(line)?.1 {
-                ParseLineResult::Input(input) => inputs.push(input),
-                ParseLineResult::Bot(bot) => bots.insert(bot.id, bot),
+        let lines = parse_lines(input, |l| parse_all(l, parse_line).map(|r| (l, r)))?;
+        let references: Vec<_> = lines.iter().flat_map(|(_, r)| r.references()).collect();
+        for (line, result) in lines {
+            match result {
+                ParseLineResult::Input(input, _) => inputs.push(input),
+                ParseLineResult::Bot(bot, _) => {
+                    let id = bot.id;
+                    if bots.insert(id, bot).is_some() {
+                        return Err(ParseError {
+                            found: format!("'bot {id}' a second time"),
+                            ..ParseError::at(
+                                input,
+                                line,
+                                "a bot that has no 'bot N gives' line yet",
+                            )
+                        });
+                    }
+                    bot_lines.insert(id, line);
+                }
+            }
+        }
+        for (r, text) in references {
+            if let OutputReference::Bot(id) = r
+                && !bots.contains(id)
+            {
+                return Err(ParseError {
+                    found: format!("'{text}'"),
+                    ..ParseError::at(input, text, "a bot that has a 'bot N gives' line")
+                });
             }
         }
 
-        Ok((inputs, bots))
+        let factory = Factory { inputs, bots };
+        // a bot can only compare two different values, which is only known once they arrive
+        if let Err((id, v)) = factory.run() {
+            return Err(ParseError {
+                found: format!("the value {v} twice"),
+                ..ParseError::at(
+                    input,
+                    bot_lines[id],
+                    format!("two different values for bot {id}"),
+                )
+            });
+        }
+        Ok(factory)
     }
 }
 
-fn main() {
-    // let input = include_str!("d10.sample.txt");
-    let input = include_str!("d10.txt");
-    let (inputs, mut bots) = parse::from_lines(input.lines()).unwrap();
-    let mut processing: VecDeque<_> = inputs
-        .iter()
-        .map(|i| match i.output {
-            Output::Value(r, v) => (r, v),
-            _ => unreachable!(),
-        })
-        .collect();
-    let mut outputs = IndexMap::new();
-    while let Some((r, v)) = processing.pop_front() {
-        match r {
-            OutputReference::Bot(id) => {
-                let b = bots
-                    .get_mut(id)
-                    .unwrap_or_else(|| panic!("Bot {id} not found"));
-                if let Some(tmp) = b.tmp_value {
-                    b.tmp_value = None;
-                    assert_ne!(v, tmp);
-                    let low = cmp::min(v, tmp);
-                    let high = cmp::max(v, tmp);
-                    processing.push_back(b.low_output.set_value(low));
-                    processing.push_back(b.high_output.set_value(high));
-                } else {
-                    b.tmp_value = Some(v);
+/// The bots and where their chips come from, before anything has been handed out.
+#[derive(Clone, Debug)]
+pub struct Factory {
+    inputs: Vec<Input>,
+    bots: IndexMap<BotId, Bot>,
+}
+
+impl Factory {
+    /// Hands out all chips until nothing moves anymore. Returns the final bots, which know what
+    /// they have compared, and the contents of the output bins. Fails with the bot and the value if
+    /// a bot is handed the same value twice, because it then can't tell low from high.
+    #[allow(clippy::type_complexity)]
+    fn run(&self) -> Result<(IndexMap<BotId, Bot>, IndexMap<OutputId, Value>), (BotId, Value)> {
+        let mut bots = self.bots.clone();
+        let mut processing: VecDeque<_> = self
+            .inputs
+            .iter()
+            .map(|i| match i.output {
+                Output::Value(r, v) => (r, v),
+                _ => unreachable!(),
+            })
+            .collect();
+        let mut outputs = IndexMap::new();
+        while let Some((r, v)) = processing.pop_front() {
+            match r {
+                OutputReference::Bot(id) => {
+                    // parsing made sure that every referenced bot exists
+                    let b = &mut bots[id];
+                    if let Some(tmp) = b.tmp_value {
+                        b.tmp_value = None;
+                        if v == tmp {
+                            return Err((id, v));
+                        }
+                        let low = cmp::min(v, tmp);
+                        let high = cmp::max(v, tmp);
+                        processing.push_back(b.low_output.set_value(low));
+                        processing.push_back(b.high_output.set_value(high));
+                    } else {
+                        b.tmp_value = Some(v);
+                    }
+                }
+                OutputReference::Output(id) => {
+                    outputs.insert(id, v);
                 }
             }
-            OutputReference::Output(id) => outputs.insert(id, v),
         }
+        Ok((bots, outputs))
     }
+}
 
-    // println!("{bots:?}");
-    // println!("{outputs:?}");
+pub struct Day10;
 
-    let important_bot = bots.values().find(|bot| {
-        matches!(
-            bot,
-            Bot {
-                low_output: Output::Value(_, 17),
-                high_output: Output::Value(_, 61),
-                ..
-            }
-        )
-    });
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    type Input<'i> = Factory;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        Ok(parse::from_lines(input)?)
+    }
 
-    println!("Important bot: {important_bot:?}");
+    fn part1(factory: &Self::Input<'_>) -> impl Display {
+        // parsing has made sure that the factory runs through
+        let (bots, _) = factory.run().unwrap();
+        let important_bot = bots.values().find(|bot| {
+            matches!(
+                bot,
+                Bot {
+                    low_output: Output::Value(_, 17),
+                    high_output: Output::Value(_, 61),
+                    ..
+                }
+            )
+        });
+        // the sample never hands out 17 and 61
+        important_bot.map_or_else(|| "none".to_string(), |bot| bot.id.to_string())
+    }
+
+    fn part2(factory: &Self::Input<'_>) -> impl Display {
+        let (_, outputs) = factory.run().unwrap();
+        // the outputs may never get a value
+        (0..3)
+            .map(|i| outputs.get(OutputId(i)).copied())
+            .product::<Option<Value>>()
+            .map_or_else(|| "none".to_string(), |p| p.to_string())
+    }
+}
 
-    println!("Part2: {}", outputs[0] * outputs[1] * outputs[2]);
+fn main() {
+    solution::main::<Day10>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc2016::ParseError;
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse::from_lines(
+                "value 5 goes to bot 2\nbot 2 gives low to bot 7 and high to output 0"
+            )
+            .unwrap_err(),
+            ParseError::new(2, 20, "a bot that has a 'bot N gives' line", "'bot 7'")
+        );
+        assert_eq!(
+            parse::from_lines(
+                "bot 2 gives low to output 0 and high to output 1\nbot 2 gives low to output 1 and high to output 0"
+            )
+            .unwrap_err(),
+            ParseError::new(
+                2,
+                1,
+                "a bot that has no 'bot N gives' line yet",
+                "'bot 2' a second time"
+            )
+        );
+        assert_eq!(
+            parse::from_lines(
+                "value 5 goes to bot 2\nvalue 5 goes to bot 2\nbot 2 gives low to output 0 and high to output 1"
+            )
+            .unwrap_err(),
+            ParseError::new(
+                3,
+                1,
+                "two different values for bot 2",
+                "the value 5 twice"
+            )
+        );
+    }
 }
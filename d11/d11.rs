@@ -1,21 +1,28 @@
+use aoc2016::parse::{parse_all, parse_lines};
+use aoc2016::solution::{self, Solution, Unsolved};
+use std::error::Error;
+use std::fmt::Display;
+
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
-struct Floor<'i> {
+pub struct Floor<'i> {
     nr: u8,
     items: Vec<&'i str>,
 }
 
 mod parse {
     use super::Floor;
+    use aoc2016::parse::IResult;
+    use nom::Parser;
     use nom::branch::alt;
     use nom::bytes::complete::tag;
     use nom::character::complete::{space0, space1};
     use nom::combinator::{eof, map, not, value};
+    use nom::error::context;
     use nom::multi::separated_list1;
-    use nom::{IResult, Parser};
     use std::cmp;
 
-    fn ordinal(s: &str) -> IResult<&str, u8> {
-        alt((
+    fn ordinal(s: &str) -> IResult<'_, u8> {
+        let ordinals = alt((
             value(1, tag("first")),
             value(2, tag("second")),
             value(3, tag("third")),
@@ -28,12 +35,12 @@ mod parse {
             value(10, tag("tenth")),
             value(11, tag("eleventh")),
             value(12, tag("twelfth")),
-        ))
-        .parse(s)
+        ));
+        context("an ordinal like 'first'", ordinals).parse(s)
     }
 
-    fn item(s: &str) -> IResult<&str, &str> {
-        let (s, _): (&str, _) = (tag("a"), space1).parse(s)?;
+    fn item(s: &str) -> IResult<'_, &str> {
+        let (s, _): (&str, _) = context("'a' and an item", (tag("a"), space1)).parse(s)?;
         let end = cmp::min(
             s.find(" and").unwrap_or(s.len()),
             cmp::min(
@@ -47,7 +54,7 @@ mod parse {
         ))
     }
 
-    fn item_list(s: &str) -> IResult<&str, Vec<&str>> {
+    fn item_list(s: &str) -> IResult<'_, Vec<&str>> {
         alt((
             map((tag("nothing"), space1, tag("relevant"), space0), |(..)| {
                 Vec::<&str>::new()
@@ -77,20 +84,20 @@ mod parse {
         .parse(s)
     }
 
-    pub fn floor(s: &'_ str) -> IResult<&'_ str, Floor<'_>> {
+    pub fn floor(s: &'_ str) -> IResult<'_, Floor<'_>> {
         map(
             (
                 space0,
-                tag("The"),
+                context("'The'", tag("The")),
                 space1,
                 ordinal,
                 space1,
-                tag("floor"),
+                context("'floor'", tag("floor")),
                 space1,
-                tag("contains"),
+                context("'contains'", tag("contains")),
                 space1,
                 item_list,
-                tag("."),
+                context("'.'", tag(".")),
                 space0,
                 eof,
             ),
@@ -127,6 +134,20 @@ mod parse {
             );
         }
 
+        #[test]
+        fn test_floor_errors() {
+            use aoc2016::ParseError;
+            use aoc2016::parse::parse_all;
+            assert_eq!(
+                parse_all("The fith floor contains nothing relevant.", floor),
+                Err(ParseError::new(1, 5, "an ordinal like 'first'", "'fith'"))
+            );
+            assert_eq!(
+                parse_all("The first floor contains a dog and cat.", floor),
+                Err(ParseError::new(1, 32, "'.'", "'and'"))
+            );
+        }
+
         #[test]
         fn test_floor() {
             assert_eq!(
@@ -185,8 +206,25 @@ mod parse {
     }
 }
 
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    type Input<'i> = Vec<Floor<'i>>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        Ok(parse_lines(input, |l| parse_all(l, parse::floor))?)
+    }
+
+    fn part1(_floors: &Self::Input<'_>) -> impl Display {
+        Unsolved
+    }
+
+    fn part2(_floors: &Self::Input<'_>) -> impl Display {
+        Unsolved
+    }
+}
+
 fn main() {
-    let input = include_str!("d11.txt");
-    let floors: Vec<_> = input.lines().map(|l| parse::floor(l).unwrap().1).collect();
-    println!("{:?}", floors);
+    solution::main::<Day11>();
 }
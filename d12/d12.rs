@@ -1,13 +1,16 @@
-#![feature(assert_matches)]
-
+use aoc2016::ParseError;
+use aoc2016::parse::{IResult, parse_all, parse_lines};
+use aoc2016::solution::{self, Solution};
+use nom::Parser;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete::{anychar, i32, i64, space1};
 use nom::combinator::{eof, map, value, verify};
-use nom::{IResult, Parser};
+use nom::error::context;
 use std::collections::HashMap;
+use std::error::Error;
 use std::fmt;
-use std::fmt::Formatter;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 type Reg = char;
@@ -63,28 +66,42 @@ impl fmt::Display for Ins {
 }
 
 impl FromStr for Ins {
-    type Err = nom::Err<nom::error::Error<String>>;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        fn reg(s: &str) -> IResult<&str, char> {
-            verify(anychar, |c| c.is_alphabetic()).parse(s)
+        fn reg(s: &str) -> IResult<'_, char> {
+            context("a register", verify(anychar, |c| c.is_alphabetic())).parse(s)
         }
 
-        fn reg_or_lit(s: &str) -> IResult<&str, RegOrLit> {
-            alt((map(reg, RegOrLit::Reg), map(i64, RegOrLit::Lit))).parse(s)
+        fn reg_or_lit(s: &str) -> IResult<'_, RegOrLit> {
+            context(
+                "a register or number",
+                alt((map(reg, RegOrLit::Reg), map(i64, RegOrLit::Lit))),
+            )
+            .parse(s)
         }
 
-        alt((
+        const INSTRUCTION: &str = "an instruction";
+        let ins = alt((
             map(
-                (tag("cpy"), space1, reg_or_lit, space1, reg),
+                (
+                    context(INSTRUCTION, tag("cpy")),
+                    space1,
+                    reg_or_lit,
+                    space1,
+                    reg,
+                ),
                 |(_, _, a, _, b)| Ins::Cpy(a, b),
             ),
             map(
                 (
-                    alt((
-                        value(Ins::Inc as fn(Reg) -> Ins, tag("inc")),
-                        value(Ins::Dec as fn(Reg) -> Ins, tag("dec")),
-                    )),
+                    context(
+                        INSTRUCTION,
+                        alt((
+                            value(Ins::Inc as fn(Reg) -> Ins, tag("inc")),
+                            value(Ins::Dec as fn(Reg) -> Ins, tag("dec")),
+                        )),
+                    ),
                     space1,
                     reg,
                     eof,
@@ -92,35 +109,37 @@ impl FromStr for Ins {
                 |(ins, _, r, _)| ins(r),
             ),
             map(
-                (tag("jnz"), space1, reg_or_lit, space1, i32, eof),
+                (
+                    context(INSTRUCTION, tag("jnz")),
+                    space1,
+                    reg_or_lit,
+                    space1,
+                    i32,
+                    eof,
+                ),
                 |(_, _, r, _, o, _)| Ins::Jnz(r, o),
             ),
-        ))
-        .parse(s)
-        .map(|(_, o)| o)
-        .map_err(<nom::Err<nom::error::Error<&str>>>::to_owned)
+        ));
+        let trimmed = s.trim();
+        parse_all(trimmed, ins).map_err(|e| e.within(s, trimmed))
     }
 }
 
-fn parse_program(s: &str) -> Result<Vec<Ins>, nom::Err<nom::error::Error<String>>> {
-    s.lines()
-        .map(str::trim)
-        .filter(|l| !l.is_empty())
-        .map(str::parse)
-        .collect()
+fn parse_program(s: &str) -> Result<Vec<Ins>, ParseError> {
+    parse_lines(s, str::parse)
 }
 
 type Val = i64;
 
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
-struct Cpu {
+pub struct Cpu {
     program: Vec<Ins>,
     pc: i32,
     registers: HashMap<Reg, Val>,
 }
 
 impl FromStr for Cpu {
-    type Err = nom::Err<nom::error::Error<String>>;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Cpu {
@@ -175,22 +194,35 @@ fn run(mut cpu: Cpu) -> Cpu {
     }
 }
 
-fn main() {
-    let cpu: Cpu = include_str!("input.asm").parse().unwrap();
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    type Input<'i> = Cpu;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        Ok(input.parse()?)
+    }
 
-    let result = run(cpu.clone());
-    println!("Part1: a = {}", result.read('a'));
+    fn part1(cpu: &Self::Input<'_>) -> impl Display {
+        run(cpu.clone()).read('a')
+    }
 
-    let mut cpu = cpu;
-    cpu.write('c', 1);
-    let result = run(cpu);
-    println!("Part2: a = {}", result.read('a'));
+    fn part2(cpu: &Self::Input<'_>) -> impl Display {
+        let mut cpu = cpu.clone();
+        cpu.write('c', 1);
+        run(cpu).read('a')
+    }
+}
+
+fn main() {
+    solution::main::<Day12>();
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::assert_matches::assert_matches;
+    use std::assert_matches;
 
     #[test]
     fn test_parse() {
@@ -202,6 +234,14 @@ mod tests {
 
         assert_matches!("cpy 1".parse::<Ins>(), Err(_));
         assert_matches!("jnz abc".parse::<Ins>(), Err(_));
+        assert_eq!(
+            "cpy 1 7".parse::<Ins>(),
+            Err(ParseError::new(1, 7, "a register", "'7'"))
+        );
+        assert_eq!(
+            parse_program("inc a\n  jmp a 2\n"),
+            Err(ParseError::new(2, 3, "an instruction", "'jmp'"))
+        );
     }
 
     #[test]
@@ -1,19 +1,19 @@
+use aoc2016::Vec2;
 use aoc2016::graph::a_star_rev;
+use aoc2016::parse::parse_value;
+use aoc2016::solution::{self, Solution};
 use std::collections::{HashSet, VecDeque};
-use vecmath::vec2_sub;
+use std::error::Error;
+use std::fmt::Display;
 
-type Pos = [u32; 2];
+type Pos = Vec2<u32>;
 
-fn is_wall([x, y]: Pos, favorite_num: u32) -> bool {
+fn is_wall(Vec2 { x, y }: Pos, favorite_num: u32) -> bool {
     (x * x + 3 * x + 2 * x * y + y + y * y + favorite_num).count_ones() & 1 == 1
 }
 
-fn neighbors([x, y]: Pos) -> impl Iterator<Item = Pos> {
-    // to not underflow, we just add 1 to everything, do the filtering against 0, then sub 1.
-    [[x, y + 1], [x + 2, y + 1], [x + 1, y], [x + 1, y + 2]]
-        .into_iter()
-        .filter(|&[x, y]| x != 0 && y != 0)
-        .map(|[x, y]| [x - 1, y - 1])
+fn neighbors(p: Pos) -> impl Iterator<Item = Pos> {
+    p.neighbors()
 }
 
 fn open_neighbors(p: Pos, favorite_num: u32) -> impl Iterator<Item = Pos> {
@@ -25,10 +25,7 @@ fn path_length(start: Pos, goal: Pos, favorite_num: u32) -> usize {
         &start,
         |&n| n == goal,
         |&n| open_neighbors(n, favorite_num).map(|n| (n, ())),
-        |&[x, y]| {
-            let vec = vec2_sub([x as i64, y as i64], [goal[0] as i64, goal[1] as i64]);
-            vec[0].abs() + vec[1].abs()
-        },
+        |&n| n.manhattan_dist(goal) as i64,
         |_, _, _| 1,
     )
     .unwrap()
@@ -56,20 +53,31 @@ fn possible_locations(start: Pos, max_depth: u32, favorite_num: u32) -> HashSet<
     visited
 }
 
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+    type Input<'i> = u32;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        Ok(parse_value(input, "a number")?)
+    }
+
+    fn part1(&favorite_num: &Self::Input<'_>) -> impl Display {
+        path_length(Vec2::new(1, 1), Vec2::new(31, 39), favorite_num)
+    }
+
+    fn part2(&favorite_num: &Self::Input<'_>) -> impl Display {
+        // aw shucks, part 2 requires dfs or bfs, they didn't assume I'd use A* for part 1
+        // So I'm gonna basically have to redo the entire algorithm which could also have been used for
+        // part 1...
+        // well, it was simple enough
+        possible_locations(Vec2::new(1, 1), 50, favorite_num).len()
+    }
+}
+
 fn main() {
-    let start = [1, 1];
-    let goal = [31, 39];
-    let favorite_num = 1362;
-    let len = path_length(start, goal, favorite_num);
-    println!("Part1: {}", len);
-
-    // aw shucks, part 2 requires dfs or bfs, they didn't assume I'd use A* for part 1
-    // So I'm gonna basically have to redo the entire algorithm which could also have been used for
-    // part 1...
-    // well, it was simple enough
-
-    let locs = possible_locations(start, 50, favorite_num);
-    println!("Part2: {}", locs.len());
+    solution::main::<Day13>();
 }
 
 #[cfg(test)]
@@ -79,7 +87,11 @@ mod tests {
 
     #[test]
     fn test_neighbors() {
-        let sut = |x: u32, y: u32| neighbors([x, y]).collect::<HashSet<_>>();
+        let sut = |x: u32, y: u32| {
+            neighbors(Vec2::new(x, y))
+                .map(<[u32; 2]>::from)
+                .collect::<HashSet<_>>()
+        };
         assert_eq!(sut(0, 0), HashSet::from([[0, 1], [1, 0]]));
         assert_eq!(sut(1, 0), HashSet::from([[1, 1], [0, 0], [2, 0]]));
         assert_eq!(sut(5, 0), HashSet::from([[5, 1], [4, 0], [6, 0]]));
@@ -91,7 +103,7 @@ mod tests {
 
     #[test]
     fn test_path_length() {
-        let l = path_length([1, 1], [7, 4], 10);
+        let l = path_length(Vec2::new(1, 1), Vec2::new(7, 4), 10);
         assert_eq!(l, 11);
     }
 }
@@ -1,9 +1,14 @@
 #![feature(bstr)]
 
-use crate::hashers::{DigestHasher, KeyHasher, MultiDigestHasher};
+use aoc2016::Json;
+use aoc2016::progress::Progress;
+use aoc2016::solution::{self, Solution};
+use hashers::{DigestHasher, KeyHasher, MultiDigestHasher};
 use itertools::Itertools;
 use md5::Md5;
 use std::bstr::ByteString;
+use std::error::Error;
+use std::fmt::Display;
 
 mod hashers {
     use md5::{Digest, Md5};
@@ -107,7 +112,9 @@ fn get_keys<K: KeyHasher>(mut key_hasher: K, count: usize) -> Vec<Key> {
     let mut out_buf = [0u8; 32];
     let mut out_buf_2 = [0u8; 32];
     let mut completed_at = None;
+    let mut progress = Progress::new("d14 hashes");
     for i in 0u64.. {
+        progress.inc();
         key_hasher.hash_hex(i, &mut out_buf);
 
         if !checking.is_empty() {
@@ -166,35 +173,76 @@ fn get_keys<K: KeyHasher>(mut key_hasher: K, count: usize) -> Vec<Key> {
     unreachable!()
 }
 
-fn debug_result(res: &[Key]) {
-    println!("index,value,hash,triple,validated_at,validated_hash");
-    for k in res {
-        println!(
-            "{},{},{},{},{},{}",
-            k.index, k.value, k.hash_hex, k.triple as char, k.validated_at, k.validation_hash,
-        );
-        assert!(k.index + 1000 >= k.validated_at);
-        assert!(
-            k.hash_hex
-                .iter()
-                .copied()
-                .tuple_windows::<(_, _, _)>()
-                .contains(&(k.triple, k.triple, k.triple))
-        )
+impl From<&Key> for Json {
+    fn from(key: &Key) -> Self {
+        Json::object([
+            ("index", Json::from(key.index)),
+            ("value", Json::from(key.value.to_string())),
+            ("hash", Json::from(key.hash_hex.to_string())),
+            ("triple", Json::from((key.triple as char).to_string())),
+            ("validated_at", Json::from(key.validated_at)),
+            (
+                "validation_hash",
+                Json::from(key.validation_hash.to_string()),
+            ),
+        ])
     }
 }
 
-fn main() {
-    // let input = b"abc";
-    let input = b"yjdafjpo";
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+    type Input<'i> = &'i [u8];
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        Ok(input.trim().as_bytes())
+    }
+
+    fn part1(salt: &Self::Input<'_>) -> impl Display {
+        let keys = get_keys(DigestHasher::<Md5>::new(salt), 64);
+        keys[63].index
+    }
 
-    println!("Running Part 1...");
-    let res = get_keys(DigestHasher::<Md5>::new(input), 64);
-    debug_result(&res);
+    fn part2(salt: &Self::Input<'_>) -> impl Display {
+        let keys = get_keys(MultiDigestHasher::<Md5>::new(salt, 2017), 64);
+        keys[63].index
+    }
+
+    /// All 64 keys of both parts.
+    fn debug(salt: &Self::Input<'_>) -> Option<Json> {
+        let part1 = get_keys(DigestHasher::<Md5>::new(salt), 64);
+        let part2 = get_keys(MultiDigestHasher::<Md5>::new(salt, 2017), 64);
+        Some(Json::object([
+            ("part1", Json::array(&part1)),
+            ("part2", Json::array(&part2)),
+        ]))
+    }
+}
 
-    println!();
+fn main() {
+    solution::main::<Day14>();
+}
 
-    println!("Running Part 2...");
-    let res = get_keys(MultiDigestHasher::<Md5>::new(input, 2017), 64);
-    debug_result(&res);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample() {
+        let keys = get_keys(DigestHasher::<Md5>::new(b"abc"), 64);
+        assert_eq!(keys[0].index, 39);
+        assert_eq!(keys[1].index, 92);
+        assert_eq!(keys[63].index, 22728);
+        for k in &keys {
+            assert!(k.index + 1000 >= k.validated_at);
+            assert!(
+                k.hash_hex
+                    .iter()
+                    .copied()
+                    .tuple_windows::<(_, _, _)>()
+                    .contains(&(k.triple, k.triple, k.triple))
+            );
+        }
+    }
 }
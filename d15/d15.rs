@@ -1,73 +1,116 @@
+use aoc2016::ParseError;
+use aoc2016::number_theory::{Congruence, crt};
+use aoc2016::parse::parse_lines;
+use aoc2016::solution::{self, Solution};
 use lazy_static::lazy_static;
 use regex::Regex;
+use std::error::Error;
+use std::fmt::Display;
 use std::str::FromStr;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
-struct Disc {
+pub struct Disc {
     id: u32,
     pos_count: u32,
     start_pos: u32,
 }
 
 impl FromStr for Disc {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        || -> Option<_> {
-            lazy_static! {
-                // language=regexp
-                static ref PAT: Regex = Regex::new(
-                    r#"Disc #(\d+) has (\d+) positions; at time=0, it is at position (\d+)."#
-                ).unwrap();
-            }
-            let (_, [id, pos_count, start_pos]) = PAT.captures(s)?.extract();
-            Some(Disc {
-                id: id.parse().ok()?,
-                pos_count: pos_count.parse().ok()?,
-                start_pos: start_pos.parse().ok()?,
-            })
-        }()
-        .ok_or(())
+        lazy_static! {
+            // language=regexp
+            static ref PAT: Regex = Regex::new(
+                r#"Disc #(\d+) has (\d+) positions; at time=0, it is at position (\d+)."#
+            ).unwrap();
+        }
+        let (_, [id, pos_count, start_pos]) = PAT
+            .captures(s)
+            .ok_or_else(|| {
+                ParseError::at(
+                    s,
+                    s.trim_start(),
+                    "'Disc #N has N positions; at time=0, it is at position N.'",
+                )
+            })?
+            .extract();
+        let num = |n: &str| n.parse().map_err(|_| ParseError::at(s, n, "a number"));
+        let disc = Disc {
+            id: num(id)?,
+            pos_count: num(pos_count)?,
+            start_pos: num(start_pos)?,
+        };
+        if disc.pos_count == 0 {
+            return Err(ParseError::at(s, pos_count, "at least one position"));
+        }
+        Ok(disc)
     }
 }
 
-fn get_first_time(discs: &[Disc]) -> u32 {
-    for i in 0.. {
-        if discs
-            .iter()
-            .all(|d| (d.start_pos + d.id + i) % d.pos_count == 0)
-        {
-            return i;
-        }
+/// The first time to press the button so the capsule falls through all discs, if there is any.
+/// Disc `d` is reached `d.id` seconds after the press, so the press time `t` has to satisfy
+/// `start_pos + id + t ≡ 0 (mod pos_count)` for every disc.
+fn get_first_time(discs: &[Disc]) -> Option<i64> {
+    let congruences = discs
+        .iter()
+        .map(|d| Congruence::new(-(d.start_pos as i64 + d.id as i64), d.pos_count as i64));
+    crt(congruences).map(|c| c.residue)
+}
+
+fn answer(time: Option<i64>) -> String {
+    time.map_or_else(|| "no solution".to_string(), |t| t.to_string())
+}
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+    type Input<'i> = Vec<Disc>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        let mut discs: Vec<Disc> = parse_lines(input, str::parse)?;
+        discs.sort_by_key(|d| d.id);
+        Ok(discs)
+    }
+
+    fn part1(discs: &Self::Input<'_>) -> impl Display {
+        answer(get_first_time(discs))
+    }
+
+    fn part2(discs: &Self::Input<'_>) -> impl Display {
+        let mut discs = discs.clone();
+        discs.push(Disc {
+            id: discs.len() as u32 + 1,
+            pos_count: 11,
+            start_pos: 0,
+        });
+        answer(get_first_time(&discs))
     }
-    panic!("didn't work");
 }
 
 fn main() {
-    // let input = include_str!("d15.sample.txt");
-    let input = include_str!("d15.txt");
-    let mut discs = input
-        .lines()
-        .map(str::parse)
-        .collect::<Result<Vec<Disc>, _>>()
-        .unwrap();
-    discs.sort_by_key(|d| d.id);
-    let t = get_first_time(&discs);
-    println!("Part1: {}", t);
-
-    discs.push(Disc {
-        id: discs.len() as u32 + 1,
-        pos_count: 11,
-        start_pos: 0,
-    });
-    let t = get_first_time(&discs);
-    println!("Part2: {}", t);
+    solution::main::<Day15>();
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_first_time() {
+        let disc = |id, pos_count, start_pos| Disc {
+            id,
+            pos_count,
+            start_pos,
+        };
+        // the example
+        assert_eq!(get_first_time(&[disc(1, 5, 4), disc(2, 2, 1)]), Some(5));
+        // 4 and 6 aren't coprime: t ≡ 3 (mod 4) and t ≡ 4 (mod 6) contradict each other
+        assert_eq!(get_first_time(&[disc(1, 4, 0), disc(2, 6, 0)]), None);
+        assert_eq!(get_first_time(&[disc(1, 4, 0), disc(2, 6, 5)]), Some(11));
+    }
+
     #[test]
     fn test_parse() {
         assert_eq!(
@@ -78,5 +121,18 @@ mod tests {
                 start_pos: 15,
             })
         );
+        assert_eq!(
+            "Disc #1 has 0 positions; at time=0, it is at position 0.".parse::<Disc>(),
+            Err(ParseError::new(1, 13, "at least one position", "'0'"))
+        );
+        assert_eq!(
+            "Disc #1 has 17 positions".parse::<Disc>(),
+            Err(ParseError::new(
+                1,
+                1,
+                "'Disc #N has N positions; at time=0, it is at position N.'",
+                "'Disc'"
+            ))
+        );
     }
 }
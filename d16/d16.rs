@@ -1,3 +1,8 @@
+use aoc2016::ParseError;
+use aoc2016::solution::{self, Solution};
+use std::error::Error;
+use std::fmt::Display;
+
 fn to_bits(s: &[u8]) -> Vec<bool> {
     s.iter().map(|&c| c == b'1').collect()
 }
@@ -41,13 +46,131 @@ fn calculate_checksum(mut data: Vec<bool>) -> Vec<bool> {
     data
 }
 
-fn main() {
-    let input = b"01000100010010111";
-    let chk = to_chars(&calculate_checksum(fill_drive(input, 272)));
-    println!("Part1: {}", unsafe { String::from_utf8_unchecked(chk) });
+/// The disk filled from `a` has the form `a d₀ b d₁ a d₂ b …`, with `b` being `a` reversed and
+/// inverted, and the `dᵢ` being the dragon curve sequence `0010011…`. That is enough to count the
+/// ones in any prefix of the disk without building it.
+struct DragonDisk {
+    /// `ones[i]` is the number of ones in the first `i` bits of `a`
+    ones: Vec<usize>,
+}
+
+impl DragonDisk {
+    fn new(start: &[u8]) -> Self {
+        let mut ones = vec![0];
+        ones.extend(start.iter().scan(0, |count, &c| {
+            *count += (c == b'1') as usize;
+            Some(*count)
+        }));
+        Self { ones }
+    }
+
+    /// Ones among the first `m` separators. `dᵢ` is 1 exactly if the odd part of `i + 1` is 3
+    /// modulo 4, and `(k + 1) / 4` of the odd numbers up to `k` are.
+    fn separator_ones(m: usize) -> usize {
+        (0..usize::BITS)
+            .map(|t| m >> t)
+            .take_while(|&k| k > 0)
+            .map(|k| (k + 1) / 4)
+            .sum()
+    }
+
+    fn ones_before(&self, pos: usize) -> usize {
+        let n = self.ones.len() - 1;
+        let (blocks, rem) = (pos / (n + 1), pos % (n + 1));
+        let ones_a = self.ones[n];
+        let ones_b = n - ones_a;
+        let partial = if blocks.is_multiple_of(2) {
+            self.ones[rem]
+        } else {
+            // the first `rem` bits of `b` are the inverted last `rem` bits of `a`
+            rem - (ones_a - self.ones[n - rem])
+        };
+        blocks.div_ceil(2) * ones_a + blocks / 2 * ones_b + Self::separator_ones(blocks) + partial
+    }
+}
 
-    let chk = to_chars(&calculate_checksum(fill_drive(input, 35651584)));
-    println!("Part2: {}", unsafe { String::from_utf8_unchecked(chk) });
+/// Every checksum bit covers a chunk of the disk whose size is the largest power of two dividing
+/// `size`, and tells whether that chunk has an even number of ones, so the checksum only needs
+/// the number of ones at the chunk boundaries. Takes O(checksum length · log size) time.
+fn streaming_checksum(start: &[u8], size: usize) -> Vec<bool> {
+    assert_ne!(size, 0, "the disk needs some space");
+    let chunk = 1 << size.trailing_zeros();
+    let disk = DragonDisk::new(start);
+    (1..=size / chunk)
+        .scan(0, |prev, i| {
+            let ones = disk.ones_before(i * chunk);
+            let odd = (ones - *prev) % 2 == 1;
+            *prev = ones;
+            // with a chunk of one bit, there is no checksum iteration to invert the parity
+            Some(if chunk == 1 { odd } else { !odd })
+        })
+        .collect()
+}
+
+/// The same as the functions on `Vec<bool>`, on packed bits. Only kept to compare against.
+#[cfg(test)]
+mod packed {
+    use bitvec::prelude::*;
+
+    pub fn transform_data(mut bits: BitVec) -> BitVec {
+        let mut tail = !bits.clone();
+        tail.reverse();
+        bits.push(false);
+        bits.extend_from_bitslice(&tail);
+        bits
+    }
+
+    pub fn fill_drive(start: &[u8], size: usize) -> BitVec {
+        let mut v: BitVec = start.iter().map(|&c| c == b'1').collect();
+        while v.len() < size {
+            v = transform_data(v);
+        }
+        v.truncate(size);
+        v
+    }
+
+    pub fn checksum_iteration(data: BitVec) -> BitVec {
+        assert!(data.len().is_multiple_of(2));
+        data.chunks_exact(2)
+            .map(|pair| pair[0] == pair[1])
+            .collect()
+    }
+
+    pub fn calculate_checksum(mut data: BitVec) -> BitVec {
+        while data.len().is_multiple_of(2) {
+            data = checksum_iteration(data);
+        }
+        data
+    }
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+    type Input<'i> = &'i [u8];
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        let input = input.trim();
+        if let Some(i) = input.chars().position(|c| c != '0' && c != '1') {
+            return Err(ParseError::at_char(input, i, "0 or 1").into());
+        }
+        Ok(input.as_bytes())
+    }
+
+    fn part1(input: &Self::Input<'_>) -> impl Display {
+        let chk = to_chars(&calculate_checksum(fill_drive(input, 272)));
+        unsafe { String::from_utf8_unchecked(chk) }
+    }
+
+    fn part2(input: &Self::Input<'_>) -> impl Display {
+        let chk = to_chars(&streaming_checksum(input, 35651584));
+        unsafe { String::from_utf8_unchecked(chk) }
+    }
+}
+
+fn main() {
+    solution::main::<Day16>();
 }
 
 #[cfg(test)]
@@ -86,5 +209,46 @@ mod tests {
             to_chars(&calculate_checksum(fill_drive(b"10000", 20))),
             b"01100"
         );
+        assert_eq!(to_chars(&streaming_checksum(b"10000", 20)), b"01100");
+    }
+
+    #[test]
+    fn test_streaming_checksum() {
+        for start in [&b"1"[..], b"0", b"10000", b"01000100010010111", b""] {
+            for size in 1..=600 {
+                assert_eq!(
+                    streaming_checksum(start, size),
+                    calculate_checksum(fill_drive(start, size)),
+                    "start {start:?}, size {size}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_packed() {
+        for start in [&b"1"[..], b"10000", b"01000100010010111"] {
+            let bits = to_bits(start);
+            assert!(
+                packed::transform_data(bits.iter().collect())
+                    .iter()
+                    .eq(&transform_data(bits))
+            );
+            for size in [2, 20, 272, 1000] {
+                let data = fill_drive(start, size);
+                let packed = packed::fill_drive(start, size);
+                assert!(packed.iter().eq(&data));
+                assert!(
+                    packed::checksum_iteration(packed.clone())
+                        .iter()
+                        .eq(&checksum_iteration(data.clone()))
+                );
+                assert!(
+                    packed::calculate_checksum(packed)
+                        .iter()
+                        .eq(&calculate_checksum(data))
+                );
+            }
+        }
     }
 }
@@ -1,7 +1,10 @@
+use aoc2016::Direction;
 use aoc2016::graph::bfs;
+use aoc2016::solution::{self, Solution};
 use md5::{Digest, Md5};
 use nom::FindSubstring;
-use vecmath::vec2_add;
+use std::error::Error;
+use std::fmt::Display;
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
 struct State {
@@ -19,34 +22,27 @@ fn get_all_paths(input: &str) -> impl IntoIterator<Item = State> {
         move |s| {
             let hash =
                 base16ct::lower::encode_string(&Md5::digest(format!("{}{}", input, s.path_taken)));
-            ['U', 'D', 'L', 'R']
-                .into_iter()
-                .enumerate()
-                .filter(|&(index, _)| "bcdef".find_substring(&hash[index..index + 1]).is_some())
-                .map(|(_, d)| d)
-                .filter(|&d| match d {
-                    'U' => s.pos[1] != 0,
-                    'D' => s.pos[1] != 3,
-                    'L' => s.pos[0] != 0,
-                    'R' => s.pos[0] != 3,
-                    _ => unreachable!(),
-                })
-                .map(|d| {
-                    let mut s = s.clone();
-                    s.path_taken.push(d);
-                    s.pos = vec2_add(
-                        s.pos,
-                        match d {
-                            'U' => [0, -1],
-                            'D' => [0, 1],
-                            'L' => [-1, 0],
-                            'R' => [1, 0],
-                            _ => unreachable!(),
-                        },
-                    );
-                    s
-                })
-                .collect::<Vec<_>>()
+            [
+                Direction::North,
+                Direction::South,
+                Direction::West,
+                Direction::East,
+            ]
+            .into_iter()
+            .enumerate()
+            .filter(|&(index, _)| "bcdef".find_substring(&hash[index..index + 1]).is_some())
+            .map(|(_, d)| d)
+            .filter(|&d| {
+                let [x, y] = d.step(s.pos, 1);
+                (0..4).contains(&x) && (0..4).contains(&y)
+            })
+            .map(|d| {
+                let mut s = s.clone();
+                s.path_taken.push(d.to_udlr().unwrap());
+                s.pos = d.step(s.pos, 1);
+                s
+            })
+            .collect::<Vec<_>>()
         },
     )
 }
@@ -62,14 +58,27 @@ fn get_longest_path(input: &str) -> State {
         .unwrap()
 }
 
-fn main() {
-    let input = "qtetzkpl";
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+    type Input<'i> = &'i str;
 
-    let p = get_shortest_path(input);
-    println!("Part1: {p:?}");
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        Ok(input.trim())
+    }
+
+    fn part1(passcode: &Self::Input<'_>) -> impl Display {
+        get_shortest_path(passcode).path_taken
+    }
 
-    let p = get_longest_path(input);
-    println!("Part2: {}", p.path_taken.len());
+    fn part2(passcode: &Self::Input<'_>) -> impl Display {
+        get_longest_path(passcode).path_taken.len()
+    }
+}
+
+fn main() {
+    solution::main::<Day17>();
 }
 
 #[cfg(test)]
@@ -1,26 +1,31 @@
+use aoc2016::ParseError;
+use aoc2016::solution::{self, Solution};
 use bitvec::prelude::*;
 use itertools::Itertools;
+use num_traits::{PrimInt, Unsigned};
+use std::error::Error;
 use std::fmt;
-use std::fmt::{Formatter, Write};
+use std::fmt::{Display, Formatter, Write};
 use std::iter::once;
 use std::str::FromStr;
 
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
-struct Row {
+pub struct Row {
     data: BitVec,
 }
 
 impl FromStr for Row {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Row {
             data: s
                 .chars()
-                .map(|c| match c {
+                .enumerate()
+                .map(|(i, c)| match c {
                     '^' => Ok(true),
                     '.' => Ok(false),
-                    _ => Err(()),
+                    _ => Err(ParseError::at_char(s, i, "'^' or '.'")),
                 })
                 .collect::<Result<_, _>>()?,
         })
@@ -88,24 +93,158 @@ impl Row {
     }
 }
 
-fn main() {
-    let input = ".^^^^^.^^^..^^^^^...^.^..^^^.^^....^.^...^^^...^^^^..^...^...^^.^.^.......^..^^...^.^.^^..^^^^^...^.";
-    let first_row: Row = input.parse().unwrap();
-    let clear_count: usize = first_row
+fn count_safe(first_row: &Row, rows: usize) -> usize {
+    first_row
         .clone()
         .into_rows()
-        .take(40)
-        .inspect(|r| println!("{}", r))
+        .take(rows)
         .map(|r| r.data.count_zeros())
-        .sum();
-    println!("Safe tiles(40): {}", clear_count);
+        .sum()
+}
 
-    let clear_count: usize = first_row
-        .into_rows()
-        .take(400000)
-        .map(|r| r.data.count_zeros())
-        .sum();
-    println!("Safe tiles(400000): {}", clear_count);
+/// A [`Row`] packed into words, so that a whole word of tiles is computed at once. Tile `i` is bit
+/// `i % W::BITS` of `words[i / W::BITS]`, and the bits past `width` are always zero.
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct PackedRow<W> {
+    words: Vec<W>,
+    width: usize,
+}
+
+impl<W: PrimInt + Unsigned> PackedRow<W> {
+    const BITS: usize = size_of::<W>() * 8;
+
+    fn new(row: &Row) -> Self {
+        let mut words = vec![W::zero(); row.data.len().div_ceil(Self::BITS)];
+        for i in row.data.iter_ones() {
+            words[i / Self::BITS] = words[i / Self::BITS] | W::one() << (i % Self::BITS);
+        }
+        Self {
+            words,
+            width: row.data.len(),
+        }
+    }
+
+    /// A tile is a trap exactly if one of its left and right neighbours is, which makes the
+    /// next row `left XOR right`, or the row shifted both ways.
+    fn next(&self) -> Self {
+        let top = Self::BITS - 1;
+        let n = self.words.len();
+        let mut words: Vec<W> = (0..n)
+            .map(|w| {
+                let word = self.words[w];
+                let below = if w > 0 {
+                    self.words[w - 1] >> top
+                } else {
+                    W::zero()
+                };
+                let above = if w + 1 < n {
+                    self.words[w + 1] << top
+                } else {
+                    W::zero()
+                };
+                // bit i of `left` is tile i - 1, and bit i of `right` is tile i + 1
+                let left = word << 1 | below;
+                let right = word >> 1 | above;
+                left ^ right
+            })
+            .collect();
+        if let Some(last) = words.last_mut()
+            && !self.width.is_multiple_of(Self::BITS)
+        {
+            *last = *last & ((W::one() << (self.width % Self::BITS)) - W::one());
+        }
+        Self {
+            words,
+            width: self.width,
+        }
+    }
+
+    fn count_safe(&self) -> usize {
+        self.width
+            - self
+                .words
+                .iter()
+                .map(|w| w.count_ones() as usize)
+                .sum::<usize>()
+    }
+}
+
+/// Like [`count_safe`], but as soon as a row repeats, the rest is extrapolated from the cycle.
+/// Rows can take very long to repeat, as the period grows exponentially with the width for some
+/// widths, so this still takes up to O(rows) steps. The cycle is found with Brent's algorithm,
+/// which only keeps two rows around instead of every row seen so far.
+fn count_safe_packed<W: PrimInt + Unsigned>(first_row: &Row, rows: usize) -> usize {
+    if rows == 0 {
+        return 0;
+    }
+    let first = PackedRow::<W>::new(first_row);
+    // the tortoise waits at powers of two for the hare to come around, which happens once the
+    // hare is in the cycle and the distance between them is at least the period
+    let (mut tortoise, mut hare) = (first.clone(), first.next());
+    let (mut power, mut period) = (1, 1);
+    // the hare is at row `i`, and `safe` counts the rows before it
+    let (mut i, mut safe) = (1, first.count_safe());
+    while tortoise != hare {
+        if i == rows {
+            return safe;
+        }
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        safe += hare.count_safe();
+        hare = hare.next();
+        i += 1;
+        period += 1;
+    }
+    // the cycle starts at the first row that is the same as the one `period` rows after it
+    let (mut tortoise, mut hare) = (first.clone(), first);
+    for _ in 0..period {
+        hare = hare.next();
+    }
+    let (mut start, mut before) = (0, 0);
+    while tortoise != hare {
+        before += tortoise.count_safe();
+        tortoise = tortoise.next();
+        hare = hare.next();
+        start += 1;
+    }
+    // the hare has gone through the whole cycle by now, so there is at least one of it
+    let (cycles, rest) = ((rows - start) / period, (rows - start) % period);
+    let (mut per_cycle, mut partial) = (0, 0);
+    for j in 0..period {
+        if j == rest {
+            partial = per_cycle;
+        }
+        per_cycle += tortoise.count_safe();
+        tortoise = tortoise.next();
+    }
+    before + cycles * per_cycle + partial
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+    type Input<'i> = Row;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        let row = input.trim();
+        Ok(row.parse().map_err(|e: ParseError| e.within(input, row))?)
+    }
+
+    fn part1(first_row: &Self::Input<'_>) -> impl Display {
+        count_safe(first_row, 40)
+    }
+
+    fn part2(first_row: &Self::Input<'_>) -> impl Display {
+        count_safe_packed::<u64>(first_row, 400000)
+    }
+}
+
+fn main() {
+    solution::main::<Day18>();
 }
 
 #[cfg(test)]
@@ -123,6 +262,10 @@ mod tests {
             })
         );
         assert_eq!(parsed.unwrap().to_string(), s);
+        assert_eq!(
+            ".^^x^".parse::<Row>(),
+            Err(ParseError::new(1, 4, "'^' or '.'", "'x^'"))
+        );
     }
 
     #[test]
@@ -135,4 +278,46 @@ mod tests {
                 .eq("..^^.\n.^^^^\n^^..^".lines())
         );
     }
+
+    #[test]
+    fn test_packed() {
+        let row: Row = ".^^.^.^^^^".parse().unwrap();
+        assert_eq!(count_safe(&row, 10), 38);
+        assert_eq!(count_safe_packed::<u64>(&row, 10), 38);
+
+        let wide: Row = "^.^^..^^^..^.^^.^^^^....^..^^.^^^..^.^^^.^^..^^..^.^^^^.^.^^..^^.^.^^.^.^^^^^.^^^^..^^.^^^^^^^.^.^..".parse().unwrap();
+        for row in [&row, &wide] {
+            let mut packed = PackedRow::<u8>::new(row);
+            for next in row.clone().into_rows().take(50) {
+                assert_eq!(PackedRow::<u8>::new(&next), packed);
+                packed = packed.next();
+            }
+        }
+    }
+
+    #[test]
+    fn test_cycle() {
+        // narrow rows repeat quickly, so this runs into the cycle for different word sizes
+        for s in ["..^^.", "^", ".^^.^.^^^^", "^....^^.^"] {
+            let row: Row = s.parse().unwrap();
+            for rows in 0..300 {
+                let expected = count_safe(&row, rows);
+                assert_eq!(
+                    count_safe_packed::<u8>(&row, rows),
+                    expected,
+                    "{s}, {rows} rows"
+                );
+                assert_eq!(
+                    count_safe_packed::<u128>(&row, rows),
+                    expected,
+                    "{s}, {rows} rows"
+                );
+            }
+        }
+        // a single trap turns into a safe tile, which then stays that way forever
+        let row: Row = "^".parse().unwrap();
+        assert_eq!(count_safe_packed::<u64>(&row, 1 << 60), (1 << 60) - 1);
+        let row: Row = ".....".parse().unwrap();
+        assert_eq!(count_safe_packed::<u64>(&row, 1 << 60), 5 << 60);
+    }
 }
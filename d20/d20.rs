@@ -1,48 +1,76 @@
-#![feature(linked_list_cursors)]
-
-use std::collections::LinkedList;
-use std::str::FromStr;
+use aoc2016::parse::parse_lines;
+use aoc2016::solution::{self, Solution};
+use aoc2016::{IntervalSet, ParseError};
+use std::error::Error;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
 
 type S = u32;
-type R = (S, S);
-
-fn blacklist_range(mut valid: LinkedList<R>, (bs, be): R) -> LinkedList<R> {
-    let mut cursor = valid.cursor_front_mut();
-    while let Some((s, e)) = cursor.current() {
-        if bs <= *s && be >= *e {
-            cursor.remove_current();
-            continue;
-        }
-        if bs <= *s && be < *e && be >= *s {
-            *s = be + 1;
-        } else if bs > *s && bs < *e && be >= *e {
-            *e = bs - 1;
-        } else if bs > *s && be < *e {
-            let old_e = *e;
-            *e = bs - 1;
-            cursor.insert_after((be + 1, old_e));
-            cursor.move_next();
-        }
-        cursor.move_next();
+type R = RangeInclusive<S>;
+
+fn parse_range(s: &str) -> Result<R, ParseError> {
+    let line = s.trim_end();
+    let (start, end) = line
+        .split_once('-')
+        .ok_or_else(|| ParseError::at(s, line, "a range like '5-8'"))?;
+    let num = |n: &str| n.parse().map_err(|_| ParseError::at(s, n, "a number"));
+    let range = num(start)?..=num(end)?;
+    if range.is_empty() {
+        return Err(ParseError::at(s, end, "an end not below the start"));
     }
-    valid
+    Ok(range)
+}
+
+fn answer(ip: Option<S>) -> String {
+    ip.map_or_else(|| "no allowed IP".to_string(), |ip| ip.to_string())
 }
 
-fn parse_range(s: &str) -> R {
-    let mut it = s.split('-').map(|p| S::from_str(p).unwrap());
-    (it.next().unwrap(), it.next().unwrap())
+pub struct Day20;
+
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+    type Input<'i> = IntervalSet<S>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        Ok(parse_lines(input, parse_range)?.into_iter().collect())
+    }
+
+    fn part1(blacklist: &Self::Input<'_>) -> impl Display {
+        answer(blacklist.first_gap(0..=S::MAX))
+    }
+
+    fn part2(blacklist: &Self::Input<'_>) -> impl Display {
+        blacklist.complement(0..=S::MAX).len()
+    }
 }
 
 fn main() {
-    let input = include_str!("d20.txt");
+    solution::main::<Day20>();
+}
 
-    let valid = input
-        .lines()
-        .map(parse_range)
-        .fold(LinkedList::from([(0, S::MAX)]), blacklist_range);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    println!("Part1: {}", valid.front().unwrap().0);
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("5-8"), Ok(5..=8));
+        assert_eq!(
+            parse_range("5-x"),
+            Err(ParseError::new(1, 3, "a number", "'x'"))
+        );
+        assert_eq!(
+            parse_range("8-5"),
+            Err(ParseError::new(1, 3, "an end not below the start", "'5'"))
+        );
+    }
 
-    let total_count: u32 = valid.iter().copied().map(|(s, e)| e + 1 - s).sum();
-    println!("Part2: {}", total_count);
+    #[test]
+    fn test_everything_blocked() {
+        let blacklist = Day20::parse("0-4000000000\n3000000000-4294967295").unwrap();
+        assert_eq!(Day20::part1(&blacklist).to_string(), "no allowed IP");
+        assert_eq!(Day20::part2(&blacklist).to_string(), "0");
+        let blacklist = Day20::parse("5-8\n0-2\n4-7").unwrap();
+        assert_eq!(Day20::part1(&blacklist).to_string(), "3");
+    }
 }
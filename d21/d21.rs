@@ -1,43 +1,32 @@
+use aoc2016::ParseError;
+use aoc2016::parse::parse_lines;
+use aoc2016::solution::{self, Solution};
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use regex::Regex;
+use std::error::Error;
+use std::fmt::Display;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
-enum Dir {
+pub enum Dir {
     Left,
     Right,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
-enum Operation {
+pub enum Operation {
     SwapIndex(usize, usize),
     SwapChar(u8, u8),
     Rotate(Dir, usize),
     RotateChar(u8),
-    /// Used for unscrambling
-    RotateCharInverse(u8),
     Reverse(usize, usize),
     Move(usize, usize),
 }
 
-impl Operation {
-    fn rev(self) -> Self {
-        match self {
-            Operation::SwapIndex(_, _) | Operation::SwapChar(_, _) | Operation::Reverse(_, _) => {
-                self
-            }
-            Operation::Rotate(dir, by) => match dir {
-                Dir::Left => Operation::Rotate(Dir::Right, by),
-                Dir::Right => Operation::Rotate(Dir::Left, by),
-            },
-            Operation::RotateChar(c) => Operation::RotateCharInverse(c),
-            Operation::RotateCharInverse(c) => Operation::RotateChar(c),
-            Operation::Move(a, b) => Operation::Move(b, a),
-        }
-    }
-}
+const PASSWORD: &[u8; 8] = b"abcdefgh";
+const SCRAMBLED: &[u8; 8] = b"fbgdceah";
 
-fn parse_op(s: &str) -> Operation {
+fn parse_op(s: &str) -> Result<Operation, ParseError> {
     lazy_static! {
         // language=regexp
         static ref PAT: Regex = Regex::new(
@@ -52,40 +41,42 @@ fn parse_op(s: &str) -> Operation {
         )
         .unwrap();
     }
-    let caps = PAT.captures(s).unwrap();
+    let caps = PAT
+        .captures(s.trim_end())
+        .ok_or_else(|| ParseError::at(s, s, "a swap, rotate, reverse or move operation"))?;
+    // positions are checked against the length of the password by `Scrambler::new`
+    let num = |name: &str| -> Result<usize, ParseError> {
+        let m = caps.name(name).unwrap().as_str();
+        m.parse().map_err(|_| ParseError::at(s, m, "a number"))
+    };
+    let letter = |name: &str| caps[name].as_bytes()[0];
     if caps.name("si").is_some() {
-        Operation::SwapIndex(
-            caps["swapIndex0"].parse().unwrap(),
-            caps["swapIndex1"].parse().unwrap(),
-        )
+        Ok(Operation::SwapIndex(num("swapIndex0")?, num("swapIndex1")?))
     } else if caps.name("sc").is_some() {
-        Operation::SwapChar(
-            caps["swapChar0"].chars().next().unwrap() as u8,
-            caps["swapChar1"].chars().next().unwrap() as u8,
-        )
+        Ok(Operation::SwapChar(
+            letter("swapChar0"),
+            letter("swapChar1"),
+        ))
     } else if caps.name("r").is_some() {
-        Operation::Rotate(
+        Ok(Operation::Rotate(
             if &caps["rotateDir"] == "left" {
                 Dir::Left
             } else {
                 Dir::Right
             },
-            caps["rotate0"].parse().unwrap(),
-        )
+            num("rotate0")?,
+        ))
     } else if caps.name("rc").is_some() {
-        Operation::RotateChar(caps["rotateChar"].chars().next().unwrap() as u8)
+        Ok(Operation::RotateChar(letter("rotateChar")))
     } else if caps.name("re").is_some() {
-        Operation::Reverse(
-            caps["reverse0"].parse().unwrap(),
-            caps["reverse1"].parse().unwrap(),
-        )
-    } else if caps.name("m").is_some() {
-        Operation::Move(
-            caps["move0"].parse().unwrap(),
-            caps["move1"].parse().unwrap(),
-        )
+        let (a, b) = (num("reverse0")?, num("reverse1")?);
+        if b < a {
+            let m = caps.name("reverse1").unwrap().as_str();
+            return Err(ParseError::at(s, m, "a position not before the first one"));
+        }
+        Ok(Operation::Reverse(a, b))
     } else {
-        panic!();
+        Ok(Operation::Move(num("move0")?, num("move1")?))
     }
 }
 
@@ -110,20 +101,6 @@ fn execute(mut s: Vec<u8>, op: Operation) -> Vec<u8> {
                 s.rotate_right(i % l)
             }
         }
-        Operation::RotateCharInverse(c) => {
-            s = (0..s.len())
-                .filter_map(|off| {
-                    let mut sc = s.clone();
-                    sc.rotate_left(off);
-                    if execute(sc.clone(), Operation::RotateChar(c)) == s {
-                        Some(sc)
-                    } else {
-                        None
-                    }
-                })
-                .next()
-                .unwrap();
-        }
         Operation::Reverse(a, b) => (&mut s)[a..=b].reverse(),
         Operation::Move(a, b) => {
             if a < b {
@@ -136,21 +113,208 @@ fn execute(mut s: Vec<u8>, op: Operation) -> Vec<u8> {
     s
 }
 
+/// Rearranges positions: `result[i] = input[self.0[i]]`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct Permutation(Vec<usize>);
+
+impl Permutation {
+    /// `None` for the operations that depend on the letters. Fails with the largest position of
+    /// `op` if it doesn't fit `len`.
+    fn of(op: Operation, len: usize) -> Option<Result<Self, usize>> {
+        let max_pos = match op {
+            Operation::SwapChar(_, _) | Operation::RotateChar(_) => return None,
+            Operation::Rotate(_, _) => 0,
+            Operation::SwapIndex(a, b) | Operation::Reverse(a, b) | Operation::Move(a, b) => {
+                a.max(b)
+            }
+        };
+        if max_pos >= len {
+            return Some(Err(max_pos));
+        }
+        // scrambling the positions themselves tells where each one ends up
+        let positions = execute((0..len).map(|i| i as u8).collect(), op);
+        Some(Ok(Self(positions.into_iter().map(usize::from).collect())))
+    }
+
+    /// First `self`, then `next`.
+    fn then(&self, next: &Self) -> Self {
+        Self(next.0.iter().map(|&i| self.0[i]).collect())
+    }
+
+    fn inverse(&self) -> Self {
+        let mut inverse = vec![0; self.0.len()];
+        for (i, &j) in self.0.iter().enumerate() {
+            inverse[j] = i;
+        }
+        Self(inverse)
+    }
+
+    fn apply(&self, s: &[u8]) -> Vec<u8> {
+        self.0.iter().map(|&i| s[i]).collect()
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+enum Step {
+    Permute(Permutation),
+    RotateChar(u8),
+}
+
+/// A list of operations compiled for passwords of a fixed length. Consecutive position-only
+/// operations are merged into a single [`Permutation`]. Letter swaps commute with everything that
+/// only moves letters around, so they are all collected into one mapping of letters applied at
+/// the end, and the letters of later rotations are renamed to what they were before the swaps.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Scrambler {
+    len: usize,
+    steps: Vec<Step>,
+    /// `letters[c]` is what the letter `c` turns into.
+    letters: Vec<u8>,
+}
+
+/// The operation at `index` refers to a `position` past the end of the password.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct OutOfRange {
+    pub index: usize,
+    pub position: usize,
+}
+
+impl Scrambler {
+    /// Panics for passwords longer than 256 letters, whose positions don't fit a byte.
+    pub fn new(operations: &[Operation], len: usize) -> Result<Self, OutOfRange> {
+        assert!(len <= 256, "passwords can have at most 256 letters");
+        let mut steps = vec![];
+        let mut letters: Vec<u8> = (0..=u8::MAX).collect();
+        for (index, &op) in operations.iter().enumerate() {
+            let permutation = Permutation::of(op, len)
+                .transpose()
+                .map_err(|position| OutOfRange { index, position })?;
+            match (permutation, op) {
+                (Some(p), _) => match steps.last_mut() {
+                    Some(Step::Permute(prev)) => *prev = prev.then(&p),
+                    _ => steps.push(Step::Permute(p)),
+                },
+                (None, Operation::SwapChar(_, _)) => letters = execute(letters, op),
+                (None, Operation::RotateChar(c)) => {
+                    let original = letters.iter().position(|&l| l == c).unwrap();
+                    steps.push(Step::RotateChar(original as u8));
+                }
+                (None, _) => unreachable!("{op:?} only moves positions"),
+            }
+        }
+        Ok(Self {
+            len,
+            steps,
+            letters,
+        })
+    }
+
+    pub fn scramble(&self, password: &[u8]) -> Vec<u8> {
+        assert_eq!(password.len(), self.len);
+        let mut s = password.to_vec();
+        for step in &self.steps {
+            s = match step {
+                Step::Permute(p) => p.apply(&s),
+                &Step::RotateChar(c) => execute(s, Operation::RotateChar(c)),
+            };
+        }
+        s.iter().map(|&c| self.letters[c as usize]).collect()
+    }
+
+    /// Whether rotating based on a letter can be undone for this length, which is whether the
+    /// letter ends up in a different position for every position it starts in.
+    fn rotation_is_invertible(len: usize) -> bool {
+        (0..len)
+            .map(|i| (2 * i + 1 + (i >= 4) as usize) % len)
+            .all_unique()
+    }
+
+    /// Whether every password with distinct letters has its own scrambled form.
+    pub fn is_invertible(&self) -> bool {
+        let rotates = self.steps.iter().any(|s| matches!(s, Step::RotateChar(_)));
+        !rotates || Self::rotation_is_invertible(self.len)
+    }
+
+    /// Every password that scrambles to `scrambled`, sorted. When all steps can be undone, this
+    /// runs them backwards, otherwise it tries all arrangements of the letters.
+    pub fn unscramble(&self, scrambled: &[u8]) -> Vec<Vec<u8>> {
+        assert_eq!(scrambled.len(), self.len);
+        let mut inverse_letters = [0; 256];
+        for (c, &l) in self.letters.iter().enumerate() {
+            inverse_letters[l as usize] = c as u8;
+        }
+        let mut s: Vec<u8> = scrambled
+            .iter()
+            .map(|&c| inverse_letters[c as usize])
+            .collect();
+        if !(self.is_invertible() && s.iter().all_unique()) {
+            let mut found: Vec<_> = s
+                .iter()
+                .copied()
+                .permutations(self.len)
+                .unique()
+                .filter(|candidate| self.scramble(candidate) == scrambled)
+                .collect();
+            found.sort();
+            return found;
+        }
+        for step in self.steps.iter().rev() {
+            s = match step {
+                Step::Permute(p) => p.inverse().apply(&s),
+                // the only rotation of `s` which gets rotated back into `s`
+                &Step::RotateChar(c) => (0..self.len)
+                    .map(|by| {
+                        let mut before = s.clone();
+                        before.rotate_left(by);
+                        before
+                    })
+                    .find(|before| execute(before.clone(), Operation::RotateChar(c)) == s)
+                    .unwrap(),
+            };
+        }
+        vec![s]
+    }
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    const DAY: u8 = 21;
+    type Input<'i> = Scrambler;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        let lines = parse_lines(input, |l| parse_op(l).map(|op| (l, op)))?;
+        let operations: Vec<_> = lines.iter().map(|&(_, op)| op).collect();
+        Scrambler::new(&operations, PASSWORD.len()).map_err(|e| {
+            let line = lines[e.index].0;
+            // point at the number, which is one of the words of the line
+            let number = line
+                .split_whitespace()
+                .find(|w| w.parse() == Ok(e.position))
+                .unwrap_or(line);
+            ParseError::at(input, number, "a position within the password").into()
+        })
+    }
+
+    fn part1(scrambler: &Self::Input<'_>) -> impl Display {
+        let result = scrambler.scramble(PASSWORD);
+        unsafe { String::from_utf8_unchecked(result) }
+    }
+
+    fn part2(scrambler: &Self::Input<'_>) -> impl Display {
+        let passwords = scrambler.unscramble(SCRAMBLED);
+        if passwords.is_empty() {
+            return "no password scrambles to that".to_string();
+        }
+        passwords
+            .into_iter()
+            .map(|p| unsafe { String::from_utf8_unchecked(p) })
+            .join(", ")
+    }
+}
+
 fn main() {
-    let input = include_str!("d21.txt");
-    let operations: Vec<_> = input.lines().map(parse_op).collect();
-    let result = operations
-        .iter()
-        .copied()
-        .fold(b"abcdefgh".to_vec(), execute);
-    println!("Part1: {}", unsafe { String::from_utf8_unchecked(result) });
-    let result = operations
-        .iter()
-        .copied()
-        .rev()
-        .map(Operation::rev)
-        .fold(b"fbgdceah".to_vec(), execute);
-    println!("Part2: {}", unsafe { String::from_utf8_unchecked(result) });
+    solution::main::<Day21>();
 }
 
 #[cfg(test)]
@@ -160,19 +324,19 @@ mod tests {
     #[test]
     fn test_parse() {
         assert_eq!(
-            parse_op("swap position 4 with position 0"),
+            parse_op("swap position 4 with position 0").unwrap(),
             Operation::SwapIndex(4, 0)
         );
         assert_eq!(
-            parse_op("swap letter d with letter b"),
+            parse_op("swap letter d with letter b").unwrap(),
             Operation::SwapChar(b'd', b'b')
         );
         assert_eq!(
-            parse_op("reverse positions 0 through 4"),
+            parse_op("reverse positions 0 through 4").unwrap(),
             Operation::Reverse(0, 4)
         );
         assert_eq!(
-            parse_op("rotate left 1 step"),
+            parse_op("rotate left 1 step").unwrap(),
             Operation::Rotate(Dir::Left, 1)
         );
     }
@@ -180,32 +344,120 @@ mod tests {
     #[test]
     fn test_op() {
         let s = b"abcde".to_vec();
-        let s = execute(s, parse_op("swap position 4 with position 0"));
+        let s = execute(s, parse_op("swap position 4 with position 0").unwrap());
         assert_eq!(s, b"ebcda");
-        let s = execute(s, parse_op("swap letter d with letter b"));
+        let s = execute(s, parse_op("swap letter d with letter b").unwrap());
         assert_eq!(s, b"edcba");
-        let s = execute(s, parse_op("reverse positions 0 through 4"));
+        let s = execute(s, parse_op("reverse positions 0 through 4").unwrap());
         assert_eq!(s, b"abcde");
-        let s = execute(s, parse_op("rotate left 1 step"));
+        let s = execute(s, parse_op("rotate left 1 step").unwrap());
         assert_eq!(s, b"bcdea");
-        let s = execute(s, parse_op("move position 1 to position 4"));
+        let s = execute(s, parse_op("move position 1 to position 4").unwrap());
         assert_eq!(s, b"bdeac");
-        let s = execute(s, parse_op("move position 3 to position 0"));
+        let s = execute(s, parse_op("move position 3 to position 0").unwrap());
         assert_eq!(s, b"abdec");
-        let s = execute(s, parse_op("rotate based on position of letter b"));
+        let s = execute(s, parse_op("rotate based on position of letter b").unwrap());
         assert_eq!(s, b"ecabd");
-        let s = execute(s, parse_op("rotate based on position of letter d"));
+        let s = execute(s, parse_op("rotate based on position of letter d").unwrap());
         assert_eq!(s, b"decab");
     }
 
+    const EXAMPLE: &str = "\
+swap position 4 with position 0
+swap letter d with letter b
+reverse positions 0 through 4
+rotate left 1 step
+move position 1 to position 4
+move position 3 to position 0
+rotate based on position of letter b
+rotate based on position of letter d";
+
     #[test]
-    fn test_inverse() {
-        let s = b"decab".to_vec();
-        let s = execute(s, parse_op("rotate based on position of letter d").rev());
-        assert_eq!(s, b"ecabd");
-        let s = execute(s, parse_op("rotate based on position of letter b").rev());
-        assert_eq!(s, b"abdec");
-        let s = execute(s, parse_op("move position 3 to position 0").rev());
-        assert_eq!(s, b"bdeac");
+    fn test_scrambler() {
+        let operations = parse_lines(EXAMPLE, parse_op).unwrap();
+        let scrambler = Scrambler::new(&operations, 5).unwrap();
+        assert_eq!(scrambler.scramble(b"abcde"), b"decab");
+        assert_eq!(
+            scrambler.scramble(b"edcba"),
+            operations.iter().copied().fold(b"edcba".to_vec(), execute)
+        );
+        // the letter based rotation is ambiguous for five letters
+        assert!(!scrambler.is_invertible());
+        let passwords = scrambler.unscramble(b"decab");
+        assert!(passwords.contains(&b"abcde".to_vec()));
+        assert!(passwords.iter().all(|p| scrambler.scramble(p) == b"decab"));
+        assert!(passwords.len() > 1);
+
+        assert!(Scrambler::new(&operations, 4).is_err());
+    }
+
+    #[test]
+    fn test_unscramble() {
+        let operations = parse_lines(EXAMPLE, parse_op).unwrap();
+        let scrambler = Scrambler::new(&operations, 8).unwrap();
+        assert!(scrambler.is_invertible());
+        for password in [b"abcdefgh", b"hgfedcba", b"bdfhaceg"] {
+            let scrambled = scrambler.scramble(password);
+            assert_eq!(
+                scrambled,
+                operations.iter().copied().fold(password.to_vec(), execute)
+            );
+            assert_eq!(scrambler.unscramble(&scrambled), [password.to_vec()]);
+        }
+        // with a repeated letter, there can be several passwords, so all of them are tried
+        let passwords = scrambler.unscramble(b"aabcdefg");
+        assert!(!passwords.is_empty());
+        assert!(
+            passwords
+                .iter()
+                .all(|p| scrambler.scramble(p) == b"aabcdefg")
+        );
+    }
+
+    #[test]
+    fn test_long_password() {
+        let operations = parse_lines(
+            "swap position 9 with position 0\n\
+             move position 2 to position 8\n\
+             reverse positions 5 through 9\n\
+             rotate based on position of letter j\n",
+            parse_op,
+        )
+        .unwrap();
+        let scrambler = Scrambler::new(&operations, 10).unwrap();
+        let password = b"abcdefghij";
+        assert_eq!(
+            scrambler.scramble(password),
+            operations.iter().copied().fold(password.to_vec(), execute)
+        );
+        assert!(Scrambler::new(&operations, PASSWORD.len()).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_op("reverse positions 4 through 2"),
+            Err(ParseError::new(
+                1,
+                29,
+                "a position not before the first one",
+                "'2'"
+            ))
+        );
+        assert_eq!(
+            parse_op("rotate up 1 step"),
+            Err(ParseError::new(
+                1,
+                1,
+                "a swap, rotate, reverse or move operation",
+                "'rotate'"
+            ))
+        );
+        assert_eq!(
+            Day21::parse("rotate left 3 steps\nswap position 4 with position 8")
+                .unwrap_err()
+                .to_string(),
+            ParseError::new(2, 31, "a position within the password", "'8'").to_string()
+        );
     }
 }
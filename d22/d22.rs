@@ -1,13 +1,18 @@
 use aoc2016::graph::a_star_rev;
-use aoc2016::vec2_hamming_dist;
+use aoc2016::parse::parse_lines;
+use aoc2016::solution::{self, Solution};
+use aoc2016::{Grid, ParseError, Vec2};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::borrow::Cow;
-use std::cmp::max;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::Display;
 use std::ops::{Index, IndexMut};
+use std::str::FromStr;
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
-struct Drive<'name> {
+pub struct Drive<'name> {
     name: Cow<'name, str>,
     pos: [usize; 2],
     size: u32,
@@ -16,7 +21,9 @@ struct Drive<'name> {
     use_percent: u8,
 }
 
-fn parse_drive(s: &str) -> Result<Drive<'_>, ()> {
+const DRIVE: &str = "a drive like '/dev/grid/node-x0-y0 85T 64T 21T 75%'";
+
+fn parse_drive(s: &str) -> Result<Drive<'_>, ParseError> {
     lazy_static! {
         // language=regexp
         static ref PAT: Regex = Regex::new(
@@ -24,15 +31,22 @@ fn parse_drive(s: &str) -> Result<Drive<'_>, ()> {
         ).unwrap();
     }
 
-    let [name, x, y, size, used, avail, use_percent] = PAT.captures(s).ok_or(())?.extract().1;
+    let [name, x, y, size, used, avail, use_percent] = PAT
+        .captures(s)
+        .ok_or_else(|| ParseError::at(s, s, DRIVE))?
+        .extract()
+        .1;
+    fn num<T: FromStr>(s: &str, n: &str) -> Result<T, ParseError> {
+        n.parse().map_err(|_| ParseError::at(s, n, "a number"))
+    }
 
     Ok(Drive {
         name: Cow::Borrowed(name),
-        pos: [x.parse().map_err(|_| ())?, y.parse().map_err(|_| ())?],
-        size: size.parse().map_err(|_| ())?,
-        used: used.parse().map_err(|_| ())?,
-        avail: avail.parse().map_err(|_| ())?,
-        use_percent: use_percent.parse().map_err(|_| ())?,
+        pos: [num(s, x)?, num(s, y)?],
+        size: num(s, size)?,
+        used: num(s, used)?,
+        avail: num(s, avail)?,
+        use_percent: num(s, use_percent)?,
     })
 }
 
@@ -42,101 +56,59 @@ impl Drive<'_> {
     }
 }
 
+/// The drives laid out by position, with `None` where the listing has no drive.
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
-struct Grid<'i> {
-    drives: Vec<Option<Drive<'i>>>,
-    width: usize,
-    height: usize,
+struct Cluster<'i> {
+    drives: Grid<Option<Drive<'i>>>,
     data_pos: [usize; 2],
 }
 
-impl<'i> Grid<'i> {
-    fn get(&self, [x, y]: [usize; 2]) -> &Option<Drive<'i>> {
-        &self.drives[y * self.width + x]
-    }
-}
-
-impl<'i> Index<[usize; 2]> for Grid<'i> {
+impl<'i> Index<[usize; 2]> for Cluster<'i> {
     type Output = Drive<'i>;
 
-    fn index(&self, [x, y]: [usize; 2]) -> &Self::Output {
-        self.drives[y * self.width + x].as_ref().unwrap()
+    fn index(&self, pos: [usize; 2]) -> &Self::Output {
+        self.drives[pos].as_ref().unwrap()
     }
 }
 
-impl<'i> IndexMut<[usize; 2]> for Grid<'i> {
-    fn index_mut(&mut self, [x, y]: [usize; 2]) -> &mut Self::Output {
-        self.drives[y * self.width + x].as_mut().unwrap()
+impl<'i> IndexMut<[usize; 2]> for Cluster<'i> {
+    fn index_mut(&mut self, pos: [usize; 2]) -> &mut Self::Output {
+        self.drives[pos].as_mut().unwrap()
     }
 }
 
-impl<'i> Grid<'i> {
+impl<'i> Cluster<'i> {
     fn construct(drives: impl IntoIterator<Item = Drive<'i>>) -> Self {
-        let mut v = Vec::new();
-        let mut width = 0;
-        let mut height = 0;
+        let drives: Vec<_> = drives.into_iter().collect();
+        let width = drives.iter().map(|d| d.pos[0] + 1).max().unwrap_or(0);
+        let height = drives.iter().map(|d| d.pos[1] + 1).max().unwrap_or(0);
+        let mut grid = Grid::from_vec(width, vec![None; width * height]);
         for drive in drives {
-            let [x, y] = drive.pos;
-            if v.len() <= y {
-                v.resize_with(y + 1, Vec::new);
-                height = max(height, y + 1);
-            }
-            let row = &mut v[y];
-            if row.len() <= x {
-                row.resize(x + 1, None);
-                width = max(width, x + 1);
-            }
-            row[x] = Some(drive);
+            // parsing made sure that no two drives have the same position
+            let pos = drive.pos;
+            grid[pos] = Some(drive);
         }
-        Grid {
-            drives: v
-                .into_iter()
-                .flat_map(|mut row| {
-                    if row.len() == width {
-                        row
-                    } else {
-                        row.resize(width, None);
-                        row
-                    }
-                })
-                .collect(),
-            width,
-            height,
+        Cluster {
+            drives: grid,
             data_pos: [0, 0],
         }
     }
-}
 
-impl<'i> Grid<'i> {
     /// returns pairs (a, b) for which a can be moved into b
     fn possible_moves(&self) -> Vec<([usize; 2], [usize; 2])> {
-        let width = self.width;
-        let height = self.height;
         let mut result = Vec::new();
-        for y in 0..height {
-            for x in 0..width {
-                let pos = [x, y];
-                let Some(cur) = self.get(pos) else {
-                    continue;
-                };
-                if cur.used == 0 {
-                    continue;
-                }
-                let x = x as isize;
-                let y = y as isize;
-                for neighbor in [[x + 1, y], [x, y + 1], [x - 1, y], [x, y - 1]]
-                    .into_iter()
-                    .filter(|&[x, y]| {
-                        (0isize..width as isize).contains(&x)
-                            && (0isize..height as isize).contains(&y)
-                    })
-                    .map(|[x, y]| [x as usize, y as usize])
+        for (pos, cur) in self.drives.iter() {
+            let Some(cur) = cur else {
+                continue;
+            };
+            if cur.used == 0 {
+                continue;
+            }
+            for neighbor in self.drives.neighbors(pos) {
+                if let Some(Some(other)) = self.drives.get(neighbor)
+                    && cur.fits_into(other)
                 {
-                    if let Some(other) = self.get(neighbor)
-                        && cur.fits_into(other)
-                    {
-                        result.push((pos, neighbor));
-                    }
+                    result.push((pos, neighbor));
                 }
             }
         }
@@ -159,13 +131,10 @@ impl<'i> Grid<'i> {
     }
 }
 
-fn find_shortest_path(grid: Grid, goal_pos: [usize; 2]) -> usize {
+fn find_shortest_path(cluster: Cluster, goal_pos: [usize; 2]) -> usize {
     a_star_rev(
-        &grid,
-        |g| {
-            println!("{:?}, {}", g.data_pos, g.possible_moves().len());
-            g.data_pos == goal_pos
-        },
+        &cluster,
+        |g| g.data_pos == goal_pos,
         |g| {
             g.possible_moves()
                 .into_iter()
@@ -173,12 +142,12 @@ fn find_shortest_path(grid: Grid, goal_pos: [usize; 2]) -> usize {
                 .collect::<Vec<_>>()
         },
         |g| {
-            let dst = vec2_hamming_dist(g.data_pos, goal_pos);
+            let dst = Vec2::from(g.data_pos).manhattan_dist(goal_pos.into());
 
             let avg_hole_dist = g
                 .possible_moves()
                 .into_iter()
-                .map(|(_, h)| vec2_hamming_dist(h, g.data_pos))
+                .map(|(_, h)| Vec2::from(h).manhattan_dist(g.data_pos.into()))
                 .fold((0, 0), |i, g| (i.0 + g, i.1 + 1));
             let avg_hole_dist = avg_hole_dist.0 / avg_hole_dist.1;
 
@@ -191,29 +160,51 @@ fn find_shortest_path(grid: Grid, goal_pos: [usize; 2]) -> usize {
     .len()
 }
 
-fn main() {
-    // let input = include_str!("d22.sample.txt");
-    let input = include_str!("d22.txt");
-    let drives: Vec<_> = input
-        .lines()
-        .skip_while(|l| !l.starts_with('/'))
-        .flat_map(parse_drive)
-        .collect();
-    let viable_count = (0..drives.len() - 1)
-        .flat_map(|a| {
-            (a + 1..drives.len())
-                .filter(move |&b| a != b)
-                .flat_map(move |b| [(a, b), (b, a)])
-        })
-        .filter(|&(a, b)| drives[a].used != 0 && drives[a].fits_into(&drives[b]))
-        .count();
-    println!("Part1: {}", viable_count);
+pub struct Day22;
 
-    let mut grid = Grid::construct(drives);
-    grid.data_pos = [grid.width - 1, 0];
+impl Solution for Day22 {
+    const DAY: u8 = 22;
+    type Input<'i> = Vec<Drive<'i>>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        // skip the shell prompt and table header in front of the drives
+        let start = input
+            .match_indices('/')
+            .map(|(i, _)| i)
+            .find(|&i| i == 0 || input.as_bytes()[i - 1] == b'\n')
+            .ok_or_else(|| ParseError::at(input, input.trim_start(), DRIVE))?;
+        let drives = &input[start..];
+        let drives = parse_lines(drives, parse_drive).map_err(|e| e.within(input, drives))?;
+        let mut positions = HashSet::new();
+        for drive in &drives {
+            if !positions.insert(drive.pos) {
+                return Err(ParseError::at(input, &drive.name, "a drive at a new position").into());
+            }
+        }
+        Ok(drives)
+    }
 
-    let l = find_shortest_path(grid, [0, 0]);
-    println!("Part2: {}", l);
+    fn part1(drives: &Self::Input<'_>) -> impl Display {
+        (0..drives.len() - 1)
+            .flat_map(|a| {
+                (a + 1..drives.len())
+                    .filter(move |&b| a != b)
+                    .flat_map(move |b| [(a, b), (b, a)])
+            })
+            .filter(|&(a, b)| drives[a].used != 0 && drives[a].fits_into(&drives[b]))
+            .count()
+    }
+
+    fn part2(drives: &Self::Input<'_>) -> impl Display {
+        let mut cluster = Cluster::construct(drives.clone());
+        cluster.data_pos = [cluster.drives.width() - 1, 0];
+
+        find_shortest_path(cluster, [0, 0])
+    }
+}
+
+fn main() {
+    solution::main::<Day22>();
 }
 
 #[cfg(test)]
@@ -235,8 +226,29 @@ mod tests {
         );
     }
 
-    fn demo_grid() -> Grid<'static> {
-        Grid::construct(
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Day22::parse("\ngarbage\n").unwrap_err().to_string(),
+            ParseError::new(2, 1, DRIVE, "'garbage'").to_string()
+        );
+        assert_eq!(
+            Day22::parse("Filesystem Size\n/dev/grid/node-x0-y0 1T")
+                .unwrap_err()
+                .to_string(),
+            ParseError::new(2, 1, DRIVE, "'/dev/grid/node-x0-y0'").to_string()
+        );
+        assert_eq!(
+            Day22::parse("/dev/grid/node-x0-y0 9T 1T 8T 11%\n/dev/grid/node-x0-y0 9T 2T 7T 22%")
+                .unwrap_err()
+                .to_string(),
+            ParseError::new(2, 1, "a drive at a new position", "'/dev/grid/node-x0-y0'")
+                .to_string()
+        );
+    }
+
+    fn demo_cluster() -> Cluster<'static> {
+        Cluster::construct(
             r#"
                 Filesystem            Size  Used  Avail  Use%
                 /dev/grid/node-x0-y0   10T    8T     2T   80%
@@ -257,17 +269,16 @@ mod tests {
     }
 
     #[test]
-    fn test_demo_grid() {
-        let grid = demo_grid();
-        assert_eq!(grid.width, 3);
-        assert_eq!(grid.height, 3);
-        assert_eq!(grid[[1, 2]].use_percent, 63);
+    fn test_demo_cluster() {
+        let cluster = demo_cluster();
+        assert_eq!(cluster.drives.width(), 3);
+        assert_eq!(cluster.drives.height(), 3);
+        assert_eq!(cluster[[1, 2]].use_percent, 63);
     }
 
     #[test]
     fn test_possible_moves() {
-        let grid = demo_grid();
-        let moves = grid.possible_moves();
+        let moves = demo_cluster().possible_moves();
         assert_eq!(
             moves,
             [
@@ -281,9 +292,9 @@ mod tests {
 
     #[test]
     fn test_execute() {
-        let grid = demo_grid().execute_move(([1, 0], [1, 1]));
+        let cluster = demo_cluster().execute_move(([1, 0], [1, 1]));
         assert_eq!(
-            grid[[1, 0]],
+            cluster[[1, 0]],
             Drive {
                 name: Cow::Borrowed("/dev/grid/node-x1-y0"),
                 pos: [1, 0],
@@ -294,7 +305,7 @@ mod tests {
             }
         );
         assert_eq!(
-            grid[[1, 1]],
+            cluster[[1, 1]],
             Drive {
                 name: Cow::Borrowed("/dev/grid/node-x1-y1"),
                 pos: [1, 1],
@@ -308,9 +319,9 @@ mod tests {
 
     #[test]
     fn test_execute_data_pos() {
-        let mut grid = demo_grid();
-        grid.data_pos = [2, 1];
-        let grid = grid.execute_move(([2, 1], [1, 1]));
-        assert_eq!(grid.data_pos, [1, 1]);
+        let mut cluster = demo_cluster();
+        cluster.data_pos = [2, 1];
+        let cluster = cluster.execute_move(([2, 1], [1, 1]));
+        assert_eq!(cluster.data_pos, [1, 1]);
     }
 }
@@ -1,13 +1,16 @@
-#![feature(assert_matches)]
-
+use aoc2016::ParseError;
+use aoc2016::parse::{IResult, parse_all, parse_lines};
+use aoc2016::solution::{self, Solution};
+use nom::Parser;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete::{anychar, i64, space0, space1};
 use nom::combinator::{map, opt, recognize, rest, value, verify};
-use nom::{IResult, Parser};
+use nom::error::context;
 use std::collections::HashMap;
+use std::error::Error;
 use std::fmt;
-use std::fmt::Formatter;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 type Reg = char;
@@ -67,35 +70,50 @@ impl fmt::Display for Ins {
 }
 
 impl FromStr for Ins {
-    type Err = nom::Err<nom::error::Error<String>>;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        fn reg(s: &str) -> IResult<&str, char> {
-            verify(anychar, |c| c.is_alphabetic()).parse(s)
+        fn reg(s: &str) -> IResult<'_, char> {
+            context("a register", verify(anychar, |c| c.is_alphabetic())).parse(s)
         }
 
-        fn reg_or_lit(s: &str) -> IResult<&str, RegOrLit> {
-            alt((map(reg, RegOrLit::Reg), map(i64, RegOrLit::Lit))).parse(s)
+        fn reg_or_lit(s: &str) -> IResult<'_, RegOrLit> {
+            context(
+                "a register or number",
+                alt((map(reg, RegOrLit::Reg), map(i64, RegOrLit::Lit))),
+            )
+            .parse(s)
         }
 
-        fn cmt(s: &str) -> IResult<&str, &str> {
+        fn cmt(s: &str) -> IResult<'_, &str> {
             (space0, recognize(opt((tag(";"), rest))))
                 .parse(s)
-                .map(|(_, c)| c)
+                .map(|(s, (_, c))| (s, c))
         }
 
-        alt((
+        const INSTRUCTION: &str = "an instruction";
+        let ins = alt((
             map(
-                (tag("cpy"), space1, reg_or_lit, space1, reg_or_lit, cmt),
+                (
+                    context(INSTRUCTION, tag("cpy")),
+                    space1,
+                    reg_or_lit,
+                    space1,
+                    reg_or_lit,
+                    cmt,
+                ),
                 |(_, _, a, _, b, _)| Ins::Cpy(a, b),
             ),
             map(
                 (
-                    alt((
-                        value(Ins::Inc as fn(Reg) -> Ins, tag("inc")),
-                        value(Ins::Dec as fn(Reg) -> Ins, tag("dec")),
-                        value(Ins::Tgl as fn(Reg) -> Ins, tag("tgl")),
-                    )),
+                    context(
+                        INSTRUCTION,
+                        alt((
+                            value(Ins::Inc as fn(Reg) -> Ins, tag("inc")),
+                            value(Ins::Dec as fn(Reg) -> Ins, tag("dec")),
+                            value(Ins::Tgl as fn(Reg) -> Ins, tag("tgl")),
+                        )),
+                    ),
                     space1,
                     reg,
                     cmt,
@@ -103,33 +121,35 @@ impl FromStr for Ins {
                 |(ins, _, r, _)| ins(r),
             ),
             map(
-                (tag("jnz"), space1, reg_or_lit, space1, reg_or_lit, cmt),
+                (
+                    context(INSTRUCTION, tag("jnz")),
+                    space1,
+                    reg_or_lit,
+                    space1,
+                    reg_or_lit,
+                    cmt,
+                ),
                 |(_, _, r, _, o, _)| Ins::Jnz(r, o),
             ),
-        ))
-        .parse(s)
-        .map(|(_, o)| o)
-        .map_err(<nom::Err<nom::error::Error<&str>>>::to_owned)
+        ));
+        let trimmed = s.trim();
+        parse_all(trimmed, ins).map_err(|e| e.within(s, trimmed))
     }
 }
 
-fn parse_program(s: &str) -> Result<Vec<Ins>, nom::Err<nom::error::Error<String>>> {
-    s.lines()
-        .map(str::trim)
-        .filter(|l| !l.is_empty())
-        .map(str::parse)
-        .collect()
+fn parse_program(s: &str) -> Result<Vec<Ins>, ParseError> {
+    parse_lines(s, str::parse)
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
-struct Cpu {
+pub struct Cpu {
     program: Vec<Ins>,
     pc: i32,
     registers: HashMap<Reg, Val>,
 }
 
 impl FromStr for Cpu {
-    type Err = nom::Err<nom::error::Error<String>>;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Cpu {
@@ -199,35 +219,53 @@ fn run(mut cpu: Cpu) -> Cpu {
         if halted {
             break cpu_next;
         }
-        // println!("{}", cpu_next.read('a'));
-        cpu_next
-            .registers
-            .iter()
-            .filter(|(_, v)| **v < 0)
-            .for_each(|(r, v)| {
-                println!("{r}={v}");
-            });
-
         cpu = cpu_next;
     }
 }
 
-fn main() {
-    let mut cpu: Cpu = include_str!("input.asm").parse().unwrap();
+pub struct Day23;
+
+impl Solution for Day23 {
+    const DAY: u8 = 23;
+    type Input<'i> = Cpu;
 
-    // cpu.write('a', 7);
-    // let result = run(cpu.clone());
-    // println!("Part1: a = {}", result.read('a'));
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        Ok(input.parse()?)
+    }
+
+    fn part1(cpu: &Self::Input<'_>) -> impl Display {
+        let mut cpu = cpu.clone();
+        cpu.write('a', 7);
+        run(cpu).read('a')
+    }
 
-    cpu.write('a', 12);
-    let result = run(cpu);
-    println!("Part2: a = {}", result.read('a'));
+    fn part2(cpu: &Self::Input<'_>) -> impl Display {
+        let mut cpu = cpu.clone();
+        cpu.write('a', 12);
+        run(cpu).read('a')
+    }
+}
+
+fn main() {
+    solution::main::<Day23>();
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_comment() {
+        assert_eq!(
+            "cpy a b   ; a=12, b=12".parse(),
+            Ok(Ins::Cpy('a'.into(), 'b'.into()))
+        );
+        assert_eq!(
+            "dec b x".parse::<Ins>(),
+            Err(ParseError::new(1, 7, "end of line", "'x'"))
+        );
+    }
+
     #[test]
     fn test_tgl_dec_inc() {
         let cpu = Cpu::from_str("cpy 1 a\ntgl a\ndec a").unwrap();
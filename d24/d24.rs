@@ -6,54 +6,28 @@
 //! On this path, we can then apply the traveling salesman problem.
 
 use aoc2016::graph::{a_star_rev, tsp};
-use aoc2016::{SymmetricalMatrix, vec2_hamming_dist};
-use std::collections::HashMap;
-use std::fmt::{Display, Formatter, Write};
-use std::iter::once;
+use aoc2016::solution::{self, Solution};
+use aoc2016::{DistanceMatrix, Grid, Json, ParseError, Vec2};
+use itertools::Itertools;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
 use std::ops::{Index, IndexMut};
-use vecmath::Vector2;
 
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
 struct Maze {
     /// wall = true, hallway = false
-    cells: Vec<bool>,
-    width: u32,
-    height: u32,
+    cells: Grid<bool>,
     /// `0` is the start
     waypoints: Vec<[i32; 2]>,
 }
 
 impl Display for Maze {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let waypoints: HashMap<_, _> = self
-            .waypoints
-            .iter()
-            .copied()
-            .enumerate()
-            .map(|(i, p)| (p, i))
-            .collect();
-        let width = self.width;
-        for y in 0..self.height {
-            if y > 0 {
-                writeln!(f)?;
-            }
-            for x in 0..width {
-                if self.cells[(y * width + x) as usize] {
-                    f.write_char('#')?;
-                } else if let Some(&wp) = waypoints.get(&[x as i32, y as i32]) {
-                    f.write_char((b'0' + wp as u8) as char)?;
-                } else {
-                    f.write_char('.')?;
-                }
-            }
+        let mut chars = self.cells.clone().map(|wall| if wall { '#' } else { '.' });
+        for (i, &p) in self.waypoints.iter().enumerate() {
+            chars[p] = (b'0' + i as u8) as char;
         }
-        Ok(())
-    }
-}
-
-impl Maze {
-    fn idx(&self, [x, y]: [i32; 2]) -> usize {
-        (y * self.width as i32 + x) as usize
+        chars.fmt(f)
     }
 }
 
@@ -61,85 +35,97 @@ impl Index<[i32; 2]> for Maze {
     type Output = bool;
 
     fn index(&self, index: [i32; 2]) -> &Self::Output {
-        &self.cells[self.idx(index)]
+        &self.cells[index]
     }
 }
 
 impl IndexMut<[i32; 2]> for Maze {
     fn index_mut(&mut self, index: [i32; 2]) -> &mut Self::Output {
-        let i = self.idx(index);
-        &mut self.cells[i]
+        &mut self.cells[index]
     }
 }
 
-fn from_lines<'i>(lines: impl IntoIterator<Item = &'i str>) -> Maze {
-    let mut it = lines.into_iter();
-    let r1 = it.next().unwrap();
-    let width = r1.len() as u32;
+/// Waypoints have to be numbered from `0` without gaps, and all of them have to be reachable.
+fn from_lines<'i>(lines: impl IntoIterator<Item = &'i str>) -> Result<Maze, ParseError> {
     let mut waypoints = Vec::new();
-    let cells: Vec<_> = once(r1)
-        .chain(it)
-        .flat_map(str::chars)
-        .enumerate()
-        .map(|(index, c): (usize, char)| match c {
-            '#' => true,
-            '.' => false,
-            '0'..='9' => {
-                let i = (c as u8 - b'0') as usize;
-                if waypoints.len() <= i {
-                    waypoints.resize(i + 1, [-1, -1]);
-                }
-                waypoints[i] = [(index as u32 % width) as i32, (index as u32 / width) as i32];
-                false
+    let cells = Grid::from_lines(lines, |[x, y], c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        '0'..='9' => {
+            let i = (c as u8 - b'0') as usize;
+            if waypoints.len() <= i {
+                waypoints.resize(i + 1, [-1, -1]);
             }
-            _ => panic!("wronge"),
-        })
-        .collect();
-    let height = cells.len() as u32 / width;
-
-    Maze {
-        cells,
-        width,
-        height,
-        waypoints,
+            waypoints[i] = [x as i32, y as i32];
+            Some(false)
+        }
+        _ => None,
+    })
+    .map_err(ParseError::from)?;
+
+    let at = |[x, y]: [i32; 2], expected: String, found: usize| {
+        ParseError::new(
+            y as usize + 1,
+            x as usize + 1,
+            expected,
+            format!("'{found}'"),
+        )
+    };
+    if waypoints.first().is_none_or(|&p| p == [-1, -1]) {
+        return Err(ParseError::new(
+            1,
+            1,
+            "a start marked with '0'",
+            "a maze without one",
+        ));
     }
+    if let Some(missing) = waypoints.iter().position(|&p| p == [-1, -1]) {
+        let last = waypoints.len() - 1;
+        return Err(at(
+            waypoints[last],
+            format!("a waypoint below {missing}, as there is no {missing}"),
+            last,
+        ));
+    }
+    let maze = Maze { cells, waypoints };
+    for (i, &p) in maze.waypoints.iter().enumerate().skip(1) {
+        if shortest_path(&maze, maze.waypoints[0], p).is_none() {
+            return Err(at(p, "a waypoint that can be reached from 0".into(), i));
+        }
+    }
+    Ok(maze)
 }
 
-fn shortest_path(maze: &Maze, from: [i32; 2], to: [i32; 2]) -> Vec<Vector2<i32>> {
+fn shortest_path(maze: &Maze, from: [i32; 2], to: [i32; 2]) -> Option<Vec<[i32; 2]>> {
     let (path, to) = a_star_rev(
         &from,
         |p| *p == to,
-        |&[x, y]| {
-            [[x + 1, y], [x, y + 1], [x - 1, y], [x, y - 1]]
-                .into_iter()
-                .filter(|&[x, y]| {
-                    (0..maze.width as i32).contains(&x) && (0..maze.height as i32).contains(&y)
-                })
-                .filter(|&p| !maze[p])
+        |&p| {
+            Vec2::from(p)
+                .neighbors()
+                .map(<[i32; 2]>::from)
+                .filter(|&p| maze.cells.get(p) == Some(&false))
                 .map(|p| (p, ()))
         },
-        |&n| vec2_hamming_dist(n, to) as i64,
+        |&n| Vec2::from(n).manhattan_dist(to.into()) as i64,
         |_, _, _| 1,
     )
-    .unwrap();
-    path.into_iter().map(|(p, _)| p).rev().chain([to]).collect()
+    .ok()?;
+    Some(path.into_iter().map(|(p, _)| p).rev().chain([to]).collect())
 }
 
-struct Graph {
+pub struct Graph {
     maze: Maze,
-    distances: SymmetricalMatrix<i32>,
+    distances: DistanceMatrix<i32>,
 }
 
 impl Graph {
     fn new(maze: Maze) -> Self {
-        let c = maze.waypoints.len();
-        let mut distances = SymmetricalMatrix::new(c);
-        for a in 0..c - 1 {
-            for b in a + 1..c {
-                distances[[a, b]] =
-                    shortest_path(&maze, maze.waypoints[a], maze.waypoints[b]).len() as i32 - 1;
-            }
-        }
+        let distances = DistanceMatrix::from_fn(maze.waypoints.len(), |a, b| {
+            // all waypoints can be reached from `0`, so they can reach each other as well
+            let path = shortest_path(&maze, maze.waypoints[a], maze.waypoints[b]).unwrap();
+            path.len() as i32 - 1
+        });
         Graph { maze, distances }
     }
 }
@@ -153,14 +139,105 @@ fn solve_tsp(graph: &Graph, include_return: bool) -> i32 {
     })
 }
 
+/// Waypoints in the order of a shortest route through all of them starting at `0`, and its
+/// length. Tries every order, which is fine for the handful of waypoints there are.
+fn best_route(graph: &Graph, include_return: bool) -> (Vec<usize>, i32) {
+    let n = graph.maze.waypoints.len();
+    (1..n)
+        .permutations(n - 1)
+        .map(|order| {
+            let mut route = vec![0];
+            route.extend(order);
+            if include_return {
+                route.push(0);
+            }
+            let len = route
+                .iter()
+                .tuple_windows()
+                .map(|(&a, &b)| graph.distances[[a, b]])
+                .sum();
+            (route, len)
+        })
+        .min_by_key(|&(_, len)| len)
+        .unwrap()
+}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    const DAY: u8 = 24;
+    type Input<'i> = Graph;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+        Ok(Graph::new(from_lines(input.lines())?))
+    }
+
+    fn part1(graph: &Self::Input<'_>) -> impl Display {
+        solve_tsp(graph, false)
+    }
+
+    fn part2(graph: &Self::Input<'_>) -> impl Display {
+        solve_tsp(graph, true)
+    }
+
+    /// The order in which the waypoints are visited in either part.
+    fn debug(graph: &Self::Input<'_>) -> Option<Json> {
+        let route = |include_return| {
+            let (route, steps) = best_route(graph, include_return);
+            Json::object([("route", Json::from(route)), ("steps", Json::from(steps))])
+        };
+        Some(Json::object([
+            ("part1", route(false)),
+            ("part2", route(true)),
+        ]))
+    }
+}
+
 fn main() {
-    // let input = include_str!("d24.sample.txt");
-    let input = include_str!("d24.txt");
-    let maze = from_lines(input.lines());
-    let graph = Graph::new(maze);
-    let shortest_path = solve_tsp(&graph, false);
-    println!("Part1: {}", shortest_path);
-
-    let shortest_path = solve_tsp(&graph, true);
-    println!("Part2: {}", shortest_path);
+    solution::main::<Day24>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_errors() {
+        let parse = |s: &str| from_lines(s.lines()).map(|m| m.waypoints);
+        assert_eq!(parse("#0.1#\n#.#2#"), Ok(vec![[1, 0], [3, 0], [3, 1]]));
+        assert_eq!(
+            parse("#1.2#"),
+            Err(ParseError::new(
+                1,
+                1,
+                "a start marked with '0'",
+                "a maze without one"
+            ))
+        );
+        assert_eq!(
+            parse("#0.2#"),
+            Err(ParseError::new(
+                1,
+                4,
+                "a waypoint below 1, as there is no 1",
+                "'2'"
+            ))
+        );
+        assert_eq!(
+            parse("#0#1#"),
+            Err(ParseError::new(
+                1,
+                4,
+                "a waypoint that can be reached from 0",
+                "'1'"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_single_waypoint() {
+        let graph = Day24::parse("#0#").unwrap();
+        assert_eq!(Day24::part1(&graph).to_string(), "0");
+        assert_eq!(Day24::part2(&graph).to_string(), "0");
+    }
 }
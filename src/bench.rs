@@ -0,0 +1,238 @@
+//! Repeated timing of the phases of a [`Solution`], so changes to a day can be measured instead
+//! of guessed at.
+
+use crate::Json;
+use crate::solution::{Solution, timed};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::hint::black_box;
+use std::io::Write;
+use std::str::FromStr;
+use std::time::Duration;
+use std::{fmt, io};
+
+/// Summary of the durations of several runs of the same phase.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+}
+
+impl Stats {
+    /// `None` if there are no samples.
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let n = sorted.len();
+        let median = if n % 2 == 1 {
+            sorted[n / 2]
+        } else {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        };
+        Some(Self {
+            runs: n,
+            min: sorted[0],
+            median,
+            mean: sorted.iter().sum::<Duration>() / n as u32,
+        })
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct BenchReport {
+    pub day: u8,
+    pub parse: Stats,
+    pub part1: Stats,
+    pub part2: Stats,
+}
+
+impl BenchReport {
+    pub fn phases(&self) -> [(&'static str, &Stats); 3] {
+        [
+            ("parse", &self.parse),
+            ("part1", &self.part1),
+            ("part2", &self.part2),
+        ]
+    }
+}
+
+/// Parses `input` and solves both parts on it, `runs` times each.
+pub fn bench<S: Solution>(input: &str, runs: usize) -> Result<BenchReport, Box<dyn Error>> {
+    assert_ne!(runs, 0, "need at least one run");
+    let mut parse = Vec::with_capacity(runs);
+    let mut part1 = Vec::with_capacity(runs);
+    let mut part2 = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let (parsed, t) = timed(|| S::parse(black_box(input)));
+        let parsed = parsed?;
+        parse.push(t);
+        part1.push(timed(|| black_box(S::part1(&parsed).to_string())).1);
+        part2.push(timed(|| black_box(S::part2(&parsed).to_string())).1);
+    }
+    Ok(BenchReport {
+        day: S::DAY,
+        parse: Stats::from_samples(&parse).unwrap(),
+        part1: Stats::from_samples(&part1).unwrap(),
+        part2: Stats::from_samples(&part2).unwrap(),
+    })
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum Format {
+    #[default]
+    Text,
+    Csv,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format '{s}', expected text, csv or json")),
+        }
+    }
+}
+
+/// Human-readable duration with three significant-ish digits, e.g. `12.3ms`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct HumanDuration(pub Duration);
+
+impl Display for HumanDuration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match self.0.as_nanos() {
+            n if n < 1_000 => format!("{n}ns"),
+            n if n < 1_000_000 => format!("{:.1}µs", n as f64 / 1e3),
+            n if n < 1_000_000_000 => format!("{:.1}ms", n as f64 / 1e6),
+            n => format!("{:.2}s", n as f64 / 1e9),
+        };
+        f.pad(&s)
+    }
+}
+
+/// Writes all reports as a table, as CSV with one row per phase, or as a JSON array with one
+/// object per phase. CSV and JSON use nanoseconds.
+pub fn write_reports(mut w: impl Write, reports: &[BenchReport], format: Format) -> io::Result<()> {
+    let rows = reports
+        .iter()
+        .flat_map(|r| r.phases().map(|(phase, stats)| (r.day, phase, stats)));
+    match format {
+        Format::Text => {
+            writeln!(
+                w,
+                "{:>3}  {:<5}  {:>4}  {:>10}  {:>10}  {:>10}",
+                "day", "phase", "runs", "min", "median", "mean"
+            )?;
+            for (day, phase, s) in rows {
+                writeln!(
+                    w,
+                    "{day:>3}  {phase:<5}  {:>4}  {:>10}  {:>10}  {:>10}",
+                    s.runs,
+                    HumanDuration(s.min),
+                    HumanDuration(s.median),
+                    HumanDuration(s.mean),
+                )?;
+            }
+        }
+        Format::Csv => {
+            writeln!(w, "day,phase,runs,min_ns,median_ns,mean_ns")?;
+            for (day, phase, s) in rows {
+                writeln!(
+                    w,
+                    "{day},{phase},{},{},{},{}",
+                    s.runs,
+                    s.min.as_nanos(),
+                    s.median.as_nanos(),
+                    s.mean.as_nanos(),
+                )?;
+            }
+        }
+        Format::Json => {
+            writeln!(w, "[")?;
+            for (i, (day, phase, s)) in rows.enumerate() {
+                if i > 0 {
+                    writeln!(w, ",")?;
+                }
+                let row = Json::object([
+                    ("day", Json::from(day)),
+                    ("phase", Json::from(phase)),
+                    ("runs", Json::from(s.runs)),
+                    ("min_ns", Json::from(s.min.as_nanos())),
+                    ("median_ns", Json::from(s.median.as_nanos())),
+                    ("mean_ns", Json::from(s.mean.as_nanos())),
+                ]);
+                write!(w, "  {row}")?;
+            }
+            writeln!(w, "\n]")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(v: u64) -> Duration {
+        Duration::from_millis(v)
+    }
+
+    #[test]
+    fn test_stats() {
+        assert_eq!(Stats::from_samples(&[]), None);
+        assert_eq!(
+            Stats::from_samples(&[ms(5), ms(1), ms(3)]),
+            Some(Stats {
+                runs: 3,
+                min: ms(1),
+                median: ms(3),
+                mean: ms(3),
+            })
+        );
+        let even = Stats::from_samples(&[ms(4), ms(1), ms(2), ms(9)]).unwrap();
+        assert_eq!(even.median, ms(3));
+        assert_eq!(even.mean, ms(4));
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let stats = Stats::from_samples(&[ms(2)]).unwrap();
+        let report = BenchReport {
+            day: 7,
+            parse: stats,
+            part1: stats,
+            part2: stats,
+        };
+        let mut out = Vec::new();
+        write_reports(&mut out, &[report], Format::Csv).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "day,phase,runs,min_ns,median_ns,mean_ns\n\
+             7,parse,1,2000000,2000000,2000000\n\
+             7,part1,1,2000000,2000000,2000000\n\
+             7,part2,1,2000000,2000000,2000000\n"
+        );
+    }
+
+    #[test]
+    fn test_human_duration() {
+        assert_eq!(
+            HumanDuration(Duration::from_nanos(999)).to_string(),
+            "999ns"
+        );
+        assert_eq!(
+            HumanDuration(Duration::from_micros(1500)).to_string(),
+            "1.5ms"
+        );
+        assert_eq!(HumanDuration(Duration::from_secs(2)).to_string(), "2.00s");
+    }
+}
@@ -0,0 +1,288 @@
+use crate::Vec2;
+use num_traits::{PrimInt, Signed};
+use std::fmt::Formatter;
+use std::str::FromStr;
+use std::{error, fmt};
+
+/// Compass direction in screen coordinates: north is `[0, -1]`, east is `[1, 0]`.
+/// This matches the orientation of [`crate::Grid`], where `y` grows downwards.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Turn {
+    Straight,
+    Left,
+    Right,
+    Around,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct InvalidDirection(pub String);
+
+impl fmt::Display for InvalidDirection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid direction '{}'", self.0)
+    }
+}
+
+impl error::Error for InvalidDirection {}
+
+impl Direction {
+    pub const CARDINAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// Clockwise index, starting at north.
+    #[inline]
+    fn eighths(self) -> i32 {
+        self as i32
+    }
+
+    #[inline]
+    pub fn is_diagonal(self) -> bool {
+        self.eighths() % 2 == 1
+    }
+
+    /// Rotates clockwise by `eighths` steps of 45°. Negative values rotate counter-clockwise.
+    #[inline]
+    pub fn rotate(self, eighths: i32) -> Self {
+        Self::ALL[(self.eighths() + eighths).rem_euclid(8) as usize]
+    }
+
+    #[inline]
+    pub fn turn_right(self) -> Self {
+        self.rotate(2)
+    }
+
+    #[inline]
+    pub fn turn_left(self) -> Self {
+        self.rotate(-2)
+    }
+
+    #[inline]
+    pub fn reverse(self) -> Self {
+        self.rotate(4)
+    }
+
+    pub fn turn(self, turn: Turn) -> Self {
+        match turn {
+            Turn::Straight => self,
+            Turn::Left => self.turn_left(),
+            Turn::Right => self.turn_right(),
+            Turn::Around => self.reverse(),
+        }
+    }
+
+    pub fn offset<S: PrimInt + Signed>(self) -> [S; 2] {
+        let [x, y] = match self {
+            Direction::North => [0, -1],
+            Direction::NorthEast => [1, -1],
+            Direction::East => [1, 0],
+            Direction::SouthEast => [1, 1],
+            Direction::South => [0, 1],
+            Direction::SouthWest => [-1, 1],
+            Direction::West => [-1, 0],
+            Direction::NorthWest => [-1, -1],
+        };
+        [S::from(x).unwrap(), S::from(y).unwrap()]
+    }
+
+    pub fn vec<S: PrimInt + Signed>(self) -> Vec2<S> {
+        self.offset().into()
+    }
+
+    /// Moves `pos` by `dist` steps into this direction.
+    pub fn step<S: PrimInt + Signed>(self, [x, y]: [S; 2], dist: S) -> [S; 2] {
+        let [dx, dy] = self.offset::<S>();
+        [x + dx * dist, y + dy * dist]
+    }
+
+    /// Parses `U`, `D`, `L`, `R` as well as `N`, `E`, `S`, `W`.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'U' | 'N' => Some(Direction::North),
+            'R' | 'E' => Some(Direction::East),
+            'D' | 'S' => Some(Direction::South),
+            'L' | 'W' => Some(Direction::West),
+            _ => None,
+        }
+    }
+
+    /// The `U`/`D`/`L`/`R` letter of a cardinal direction.
+    pub fn to_udlr(self) -> Option<char> {
+        match self {
+            Direction::North => Some('U'),
+            Direction::East => Some('R'),
+            Direction::South => Some('D'),
+            Direction::West => Some('L'),
+            _ => None,
+        }
+    }
+
+    pub fn to_compass(self) -> &'static str {
+        match self {
+            Direction::North => "N",
+            Direction::NorthEast => "NE",
+            Direction::East => "E",
+            Direction::SouthEast => "SE",
+            Direction::South => "S",
+            Direction::SouthWest => "SW",
+            Direction::West => "W",
+            Direction::NorthWest => "NW",
+        }
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = InvalidDirection;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Self::from_char(value).ok_or_else(|| InvalidDirection(value.to_string()))
+    }
+}
+
+impl FromStr for Direction {
+    type Err = InvalidDirection;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return c.try_into();
+        }
+        Self::ALL
+            .into_iter()
+            .find(|d| d.to_compass() == s)
+            .ok_or_else(|| InvalidDirection(s.to_string()))
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.to_compass())
+    }
+}
+
+impl Turn {
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'L' => Some(Turn::Left),
+            'R' => Some(Turn::Right),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<char> for Turn {
+    type Error = InvalidDirection;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Self::from_char(value).ok_or_else(|| InvalidDirection(value.to_string()))
+    }
+}
+
+/// A position together with a heading, moved around by turning and walking forward.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Turtle<S = i32> {
+    pub pos: [S; 2],
+    pub heading: Direction,
+}
+
+impl<S: PrimInt + Signed> Default for Turtle<S> {
+    fn default() -> Self {
+        Self::new([S::zero(), S::zero()], Direction::North)
+    }
+}
+
+impl<S: PrimInt + Signed> Turtle<S> {
+    pub fn new(pos: [S; 2], heading: Direction) -> Self {
+        Self { pos, heading }
+    }
+
+    pub fn turn(&mut self, turn: Turn) {
+        self.heading = self.heading.turn(turn);
+    }
+
+    pub fn forward(&mut self, dist: S) {
+        self.pos = self.heading.step(self.pos, dist);
+    }
+
+    /// Moves into `dir` without changing the heading.
+    pub fn slide(&mut self, dir: Direction, dist: S) {
+        self.pos = dir.step(self.pos, dist);
+    }
+
+    /// Manhattan distance to `other`.
+    pub fn distance_to(&self, other: [S; 2]) -> S {
+        crate::Vec2::from(self.pos).manhattan_dist(other.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turn() {
+        assert_eq!(Direction::North.turn_right(), Direction::East);
+        assert_eq!(Direction::North.turn_left(), Direction::West);
+        assert_eq!(Direction::West.turn(Turn::Right), Direction::North);
+        assert_eq!(Direction::SouthEast.reverse(), Direction::NorthWest);
+        assert_eq!(Direction::North.rotate(-1), Direction::NorthWest);
+        assert_eq!(Direction::North.rotate(17), Direction::NorthEast);
+        assert_eq!(
+            Direction::NorthWest.vec::<i32>().rotate_cw(),
+            Direction::NorthEast.vec()
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Direction::try_from('U'), Ok(Direction::North));
+        assert_eq!(Direction::try_from('W'), Ok(Direction::West));
+        assert_eq!("SE".parse(), Ok(Direction::SouthEast));
+        assert_eq!("D".parse(), Ok(Direction::South));
+        assert!("X".parse::<Direction>().is_err());
+        assert_eq!(Direction::South.to_udlr(), Some('D'));
+        assert_eq!(Direction::SouthEast.to_udlr(), None);
+    }
+
+    #[test]
+    fn test_turtle() {
+        let mut t = Turtle::default();
+        t.turn(Turn::Right);
+        t.forward(5);
+        t.turn(Turn::Left);
+        t.forward(5);
+        t.turn(Turn::Right);
+        t.forward(5);
+        t.turn(Turn::Right);
+        t.forward(3);
+        assert_eq!(t.pos, [10, -2]);
+        assert_eq!(t.distance_to([0, 0]), 12);
+        assert_eq!(t.heading, Direction::South);
+    }
+}
@@ -3,7 +3,7 @@ pub use a_star::a_star_rev;
 use std::cmp;
 use std::collections::HashMap;
 
-pub use bfs_impl::bfs;
+pub use bfs_impl::{Bfs, INode, bfs};
 
 mod a_star {
     use std::collections::{HashMap, HashSet};
@@ -105,14 +105,15 @@ mod a_star {
                 &[2, 5],    // 4
                 &[0, 2, 4], // 5
             ];
+            let dist = |a: [f64; 2], b: [f64; 2]| (a[0] - b[0]).hypot(a[1] - b[1]);
             let start = 0;
             let goal = 2;
             let result = a_star_rev(
                 &start,
                 |n| *n == goal,
                 |a| neighbors[*a].iter().map(|b| (*b, ())).collect::<Vec<_>>(),
-                |a| vecmath::vec2_len(vecmath::vec2_sub(points[*a], points[goal])) as i64,
-                |a, _, b| vecmath::vec2_len(vecmath::vec2_sub(points[*a], points[*b])) as i64,
+                |a| dist(points[*a], points[goal]) as i64,
+                |a, _, b| dist(points[*a], points[*b]) as i64,
             )
             .unwrap()
             .0;
@@ -126,17 +127,25 @@ mod bfs_impl {
     use std::collections::{HashSet, VecDeque};
     use std::hash::Hash;
 
-    trait INode: Clone + Hash + Eq {}
+    /// What [`Bfs`] needs of its nodes to remember which ones it has seen. Implemented for every
+    /// type that is `Clone + Hash + Eq`.
+    pub trait INode: Clone + Hash + Eq {}
     impl<T: Clone + Hash + Eq> INode for T {}
 
-    struct BfsIter<
+    /// Breadth-first search over an implicit graph, yielding every node that satisfies `is_goal`
+    /// in order of increasing depth.
+    ///
+    /// Goal nodes are not expanded any further. Since this is a regular iterator, the search can be
+    /// stopped at any point, inspected, and resumed by calling [`Iterator::next`] again.
+    pub struct Bfs<
         Node: INode,
         IsGoal: Fn(&Node) -> bool,
         Neighbors: IntoIterator<Item = Node>,
         GetNeighbors: Fn(&Node) -> Neighbors,
     > {
         explored_set: HashSet<Node>,
-        frontier: VecDeque<Node>,
+        frontier: VecDeque<(Node, usize)>,
+        depth: usize,
         is_goal: IsGoal,
         get_neighbors: GetNeighbors,
     }
@@ -146,16 +155,62 @@ mod bfs_impl {
         IsGoal: Fn(&Node) -> bool,
         Neighbors: IntoIterator<Item = Node>,
         GetNeighbors: Fn(&Node) -> Neighbors,
-    > BfsIter<Node, IsGoal, Neighbors, GetNeighbors>
+    > Bfs<Node, IsGoal, Neighbors, GetNeighbors>
     {
-        fn new(start: Node, is_goal: IsGoal, get_neighbors: GetNeighbors) -> Self {
+        pub fn new(start: Node, is_goal: IsGoal, get_neighbors: GetNeighbors) -> Self {
+            Self::with_starts([start], is_goal, get_neighbors)
+        }
+
+        /// Seeds the search with several start nodes, all of which are at depth 0.
+        /// Duplicates are only visited once.
+        pub fn with_starts(
+            starts: impl IntoIterator<Item = Node>,
+            is_goal: IsGoal,
+            get_neighbors: GetNeighbors,
+        ) -> Self {
+            let mut explored_set = HashSet::new();
+            let frontier = starts
+                .into_iter()
+                .filter(|s| explored_set.insert(s.clone()))
+                .map(|s| (s, 0))
+                .collect();
             Self {
-                explored_set: HashSet::from([start.clone()]),
-                frontier: VecDeque::from([start]),
+                explored_set,
+                frontier,
+                depth: 0,
                 is_goal,
                 get_neighbors,
             }
         }
+
+        /// Number of nodes that have been discovered so far, including the ones still waiting in
+        /// the frontier.
+        #[inline]
+        pub fn explored_count(&self) -> usize {
+            self.explored_set.len()
+        }
+
+        #[inline]
+        pub fn is_explored(&self, node: &Node) -> bool {
+            self.explored_set.contains(node)
+        }
+
+        /// Depth of the node that was visited last. The start nodes have depth 0.
+        #[inline]
+        pub fn depth(&self) -> usize {
+            self.depth
+        }
+
+        /// Nodes that have been discovered but not visited yet, together with their depth.
+        #[inline]
+        pub fn frontier(&self) -> impl Iterator<Item = (&Node, usize)> {
+            self.frontier.iter().map(|(n, d)| (n, *d))
+        }
+
+        #[inline]
+        pub fn is_exhausted(&self) -> bool {
+            self.frontier.is_empty()
+        }
     }
 
     impl<
@@ -163,12 +218,13 @@ mod bfs_impl {
         IsGoal: Fn(&Node) -> bool,
         Neighbors: IntoIterator<Item = Node>,
         GetNeighbors: Fn(&Node) -> Neighbors,
-    > Iterator for BfsIter<Node, IsGoal, Neighbors, GetNeighbors>
+    > Iterator for Bfs<Node, IsGoal, Neighbors, GetNeighbors>
     {
         type Item = Node;
 
         fn next(&mut self) -> Option<Self::Item> {
-            while let Some(current) = self.frontier.pop_front() {
+            while let Some((current, depth)) = self.frontier.pop_front() {
+                self.depth = depth;
                 if (self.is_goal)(&current) {
                     return Some(current);
                 }
@@ -177,7 +233,7 @@ mod bfs_impl {
                         continue;
                     }
                     self.explored_set.insert(n.clone());
-                    self.frontier.push_back(n);
+                    self.frontier.push_back((n, depth + 1));
                 }
             }
 
@@ -185,7 +241,6 @@ mod bfs_impl {
         }
     }
 
-    #[allow(private_bounds)]
     pub fn bfs<
         Node: INode,
         IsGoal: Fn(&Node) -> bool,
@@ -195,12 +250,58 @@ mod bfs_impl {
         start: Node,
         is_goal: IsGoal,
         get_neighbors: GetNeighbors,
-    ) -> impl IntoIterator<Item = Node> {
-        BfsIter::new(start, is_goal, get_neighbors)
+    ) -> Bfs<Node, IsGoal, Neighbors, GetNeighbors> {
+        Bfs::new(start, is_goal, get_neighbors)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn line_neighbors(&n: &i32) -> Vec<i32> {
+            [n - 1, n + 1]
+                .into_iter()
+                .filter(|n| (0..10).contains(n))
+                .collect()
+        }
+
+        #[test]
+        fn test_bfs() {
+            // goals are not expanded, so nothing below 4 is ever reached.
+            let found: Vec<_> = bfs(5, |n| n % 4 == 0, line_neighbors).collect();
+            assert_eq!(found, [4, 8]);
+        }
+
+        #[test]
+        fn test_resume() {
+            let mut search = bfs(4, |&n| n == 2 || n == 7, line_neighbors);
+            assert_eq!(search.next(), Some(2));
+            assert_eq!(search.depth(), 2);
+            assert_eq!(search.explored_count(), 5);
+            assert_eq!(search.frontier().collect::<Vec<_>>(), [(&6, 2)]);
+            assert!(!search.is_explored(&7));
+            assert_eq!(search.next(), Some(7));
+            assert_eq!(search.depth(), 3);
+            assert_eq!(search.next(), None);
+            assert!(search.is_exhausted());
+        }
+
+        #[test]
+        fn test_multiple_starts() {
+            let mut search = Bfs::with_starts([0, 9, 0], |&n| n == 4, line_neighbors);
+            assert_eq!(search.frontier().count(), 2);
+            assert_eq!(search.next(), Some(4));
+            assert_eq!(search.depth(), 4);
+        }
     }
 }
 
 pub fn tsp(n: u16, dist: impl Fn(u16, u16) -> i32) -> i32 {
+    assert_ne!(n, 0, "there has to be a node to start at");
+    // there is nowhere to go from the only node
+    if n == 1 {
+        return 0;
+    }
     let mut g = HashMap::new();
     for k in 0..n {
         g.insert((1u64 << k, k), dist(0, k));
@@ -0,0 +1,433 @@
+use crate::ParseError;
+use num_traits::ToPrimitive;
+use std::fmt::{Formatter, Write};
+use std::ops::{Index, IndexMut};
+use std::{error, fmt};
+
+/// Dense, row-major 2D grid. Positions are `[x, y]`, with `y` growing downwards.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ParseGridError {
+    /// The cell mapping rejected the character at this position.
+    InvalidCell { pos: [usize; 2], c: char },
+    /// A row doesn't have the same width as the first one.
+    RaggedRow { y: usize, width: usize },
+}
+
+impl fmt::Display for ParseGridError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseGridError::InvalidCell { pos: [x, y], c } => {
+                write!(f, "invalid cell '{c}' at x={x}, y={y}")
+            }
+            ParseGridError::RaggedRow { y, width } => {
+                write!(
+                    f,
+                    "row {y} has a width of {width}, expected the width of row 0"
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for ParseGridError {}
+
+/// Positions are relative to the lines the grid was built from.
+impl From<ParseGridError> for ParseError {
+    fn from(e: ParseGridError) -> Self {
+        match e {
+            ParseGridError::InvalidCell { pos: [x, y], c } => {
+                ParseError::new(y + 1, x + 1, "a valid cell", format!("'{c}'"))
+            }
+            ParseGridError::RaggedRow { y, width } => ParseError::new(
+                y + 1,
+                1,
+                "a row as wide as the first one",
+                format!("a row of width {width}"),
+            ),
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self {
+            cells: vec![value; width * height],
+            width,
+            height,
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// panics if `cells` can't be split into rows of length `width`.
+    pub fn from_vec(width: usize, cells: Vec<T>) -> Self {
+        assert!(width != 0 || cells.is_empty());
+        assert!(cells.len().is_multiple_of(width.max(1)));
+        Self {
+            height: cells.len().checked_div(width).unwrap_or(0),
+            cells,
+            width,
+        }
+    }
+
+    /// Builds a grid from character art, one line per row. `cell` receives the position and the
+    /// character, and returns `None` if the character is invalid.
+    pub fn from_lines<'s>(
+        lines: impl IntoIterator<Item = &'s str>,
+        mut cell: impl FnMut([usize; 2], char) -> Option<T>,
+    ) -> Result<Self, ParseGridError> {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for (y, line) in lines.into_iter().enumerate() {
+            let len_before = cells.len();
+            for (x, c) in line.chars().enumerate() {
+                let value =
+                    cell([x, y], c).ok_or(ParseGridError::InvalidCell { pos: [x, y], c })?;
+                cells.push(value);
+            }
+            let w = cells.len() - len_before;
+            if *width.get_or_insert(w) != w {
+                return Err(ParseGridError::RaggedRow { y, width: w });
+            }
+            height += 1;
+        }
+        Ok(Self {
+            cells,
+            width: width.unwrap_or(0),
+            height,
+        })
+    }
+
+    /// Like [`Grid::from_lines`], but trims every line and skips empty ones, so indented raw
+    /// string literals can be used directly.
+    pub fn parse_with(
+        s: &str,
+        cell: impl FnMut([usize; 2], char) -> Option<T>,
+    ) -> Result<Self, ParseGridError> {
+        Self::from_lines(s.lines().map(str::trim).filter(|l| !l.is_empty()), cell)
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[inline]
+    fn idx(&self, [x, y]: [impl ToPrimitive; 2]) -> Option<usize> {
+        let x = x.to_usize()?;
+        let y = y.to_usize()?;
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Accepts signed coordinates, anything outside the grid is `false`.
+    #[inline]
+    pub fn contains(&self, pos: [impl ToPrimitive; 2]) -> bool {
+        self.idx(pos).is_some()
+    }
+
+    #[inline]
+    pub fn get(&self, pos: [impl ToPrimitive; 2]) -> Option<&T> {
+        self.idx(pos).map(|i| &self.cells[i])
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, pos: [impl ToPrimitive; 2]) -> Option<&mut T> {
+        self.idx(pos).map(|i| &mut self.cells[i])
+    }
+
+    #[inline]
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    #[inline]
+    pub fn into_cells(self) -> Vec<T> {
+        self.cells
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = [usize; 2]> + use<T> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| [x, y]))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ([usize; 2], &T)> {
+        self.positions().zip(&self.cells)
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = ([usize; 2], &mut T)> {
+        self.positions().zip(&mut self.cells)
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.into_iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    #[inline]
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    #[inline]
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = &[T]> {
+        // chunks_exact would panic for a width of 0.
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl ExactSizeIterator<Item = &T> {
+        assert!(x < self.width);
+        (0..self.height).map(move |y| &self.cells[y * self.width + x])
+    }
+
+    pub fn columns(&self) -> impl ExactSizeIterator<Item = impl ExactSizeIterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    pub fn rotate_row_right(&mut self, y: usize, by: usize) {
+        let width = self.width;
+        if width != 0 {
+            self.row_mut(y).rotate_right(by % width);
+        }
+    }
+
+    pub fn rotate_row_left(&mut self, y: usize, by: usize) {
+        let width = self.width;
+        if width != 0 {
+            self.row_mut(y).rotate_left(by % width);
+        }
+    }
+
+    /// Moves every cell of column `x` down by `by`, wrapping around at the bottom.
+    pub fn rotate_column_down(&mut self, x: usize, by: usize) {
+        assert!(x < self.width);
+        let by = match self.height {
+            0 => return,
+            h => by % h,
+        };
+        if by == 0 {
+            return;
+        }
+        // cycle decomposition of the rotation, so nothing has to be cloned or buffered.
+        let (width, height) = (self.width, self.height);
+        let cycles = gcd(by, height);
+        for start in 0..cycles {
+            let mut y = start;
+            loop {
+                let next = (y + by) % height;
+                if next == start {
+                    break;
+                }
+                self.cells.swap(start * width + x, next * width + x);
+                y = next;
+            }
+        }
+    }
+
+    pub fn rotate_column_up(&mut self, x: usize, by: usize) {
+        let height = self.height.max(1);
+        self.rotate_column_down(x, height - by % height);
+    }
+
+    /// Orthogonally adjacent positions that lie inside the grid.
+    pub fn neighbors(&self, [x, y]: [usize; 2]) -> impl Iterator<Item = [usize; 2]> + use<T> {
+        let (width, height) = (self.width, self.height);
+        [[0, -1], [1, 0], [0, 1], [-1, 0]]
+            .into_iter()
+            .filter_map(move |d| offset([x, y], d, width, height))
+    }
+
+    /// Orthogonally and diagonally adjacent positions that lie inside the grid.
+    pub fn neighbors_with_diagonals(
+        &self,
+        [x, y]: [usize; 2],
+    ) -> impl Iterator<Item = [usize; 2]> + use<T> {
+        let (width, height) = (self.width, self.height);
+        [
+            [0, -1],
+            [1, -1],
+            [1, 0],
+            [1, 1],
+            [0, 1],
+            [-1, 1],
+            [-1, 0],
+            [-1, -1],
+        ]
+        .into_iter()
+        .filter_map(move |d| offset([x, y], d, width, height))
+    }
+
+    /// Renders the grid with one character per cell and a newline between rows.
+    pub fn display_with<F: Fn(&T) -> char>(&self, cell: F) -> GridDisplay<'_, T, F> {
+        GridDisplay { grid: self, cell }
+    }
+}
+
+fn offset(
+    [x, y]: [usize; 2],
+    [dx, dy]: [isize; 2],
+    width: usize,
+    height: usize,
+) -> Option<[usize; 2]> {
+    let x = x.checked_add_signed(dx)?;
+    let y = y.checked_add_signed(dy)?;
+    (x < width && y < height).then_some([x, y])
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+impl<T, S: ToPrimitive> Index<[S; 2]> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, index: [S; 2]) -> &Self::Output {
+        let i = self.idx(index).expect("position out of bounds");
+        &self.cells[i]
+    }
+}
+
+impl<T, S: ToPrimitive> IndexMut<[S; 2]> for Grid<T> {
+    fn index_mut(&mut self, index: [S; 2]) -> &mut Self::Output {
+        let i = self.idx(index).expect("position out of bounds");
+        &mut self.cells[i]
+    }
+}
+
+pub struct GridDisplay<'g, T, F: Fn(&T) -> char> {
+    grid: &'g Grid<T>,
+    cell: F,
+}
+
+impl<T, F: Fn(&T) -> char> fmt::Display for GridDisplay<'_, T, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.grid.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            row.iter()
+                .map(&self.cell)
+                .try_for_each(|c| f.write_char(c))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Grid<char> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.display_with(|&c| c).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn walls(s: &str) -> Grid<bool> {
+        Grid::parse_with(s, |_, c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_display() {
+        let grid = walls(
+            r"
+                ##..
+                .#.#
+                ....
+            ",
+        );
+        assert_eq!(grid.width(), 4);
+        assert_eq!(grid.height(), 3);
+        assert!(grid[[3, 1]]);
+        assert!(!grid[[3, 2]]);
+        assert_eq!(
+            grid.display_with(|&b| if b { '#' } else { '.' })
+                .to_string(),
+            "##..\n.#.#\n...."
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Grid::<bool>::parse_with("#.\n.x", |_, c| (c != 'x').then_some(c == '#')),
+            Err(ParseGridError::InvalidCell {
+                pos: [1, 1],
+                c: 'x'
+            })
+        );
+        assert_eq!(
+            Grid::parse_with("##\n###", |_, c| Some(c)),
+            Err(ParseGridError::RaggedRow { y: 1, width: 3 })
+        );
+    }
+
+    #[test]
+    fn test_get() {
+        let grid = Grid::from_vec(2, vec![1, 2, 3, 4]);
+        assert_eq!(grid.get([1, 1]), Some(&4));
+        assert_eq!(grid.get([2, 0]), None);
+        assert_eq!(grid.get([-1i32, 0]), None);
+        assert!(!grid.contains([0, 2]));
+    }
+
+    #[test]
+    fn test_rows_columns() {
+        let grid = Grid::from_vec(3, vec![1, 2, 3, 4, 5, 6]);
+        assert!(grid.rows().eq([[1, 2, 3], [4, 5, 6]]));
+        assert!(grid.column(1).eq(&[2, 5]));
+        assert_eq!(
+            grid.columns().map(|c| c.sum()).collect::<Vec<i32>>(),
+            [5, 7, 9]
+        );
+    }
+
+    #[test]
+    fn test_rotate() {
+        let mut grid = Grid::from_vec(3, (0..12).collect());
+        grid.rotate_row_right(1, 4);
+        assert_eq!(grid.row(1), [5, 3, 4]);
+        grid.rotate_row_left(1, 1);
+        assert_eq!(grid.row(1), [3, 4, 5]);
+        grid.rotate_column_down(2, 2);
+        assert!(grid.column(2).copied().eq([8, 11, 2, 5]));
+        grid.rotate_column_up(2, 6);
+        assert!(grid.column(2).copied().eq([2, 5, 8, 11]));
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::new(3, 2, ());
+        assert_eq!(grid.neighbors([0, 0]).collect::<Vec<_>>(), [[1, 0], [0, 1]]);
+        assert_eq!(grid.neighbors([1, 1]).count(), 3);
+        assert_eq!(grid.neighbors_with_diagonals([1, 0]).count(), 5);
+    }
+}
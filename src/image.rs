@@ -0,0 +1,301 @@
+//! Writes black and white pictures of grids, as PBM images or animated GIFs, so screens that
+//! puzzles draw can be looked at outside the terminal.
+//!
+//! Both formats are simple enough to write by hand: PBM is a header followed by one `0` or `1`
+//! per pixel, and GIF only needs its LZW compression on top of a few fixed blocks.
+
+use crate::Grid;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// Writes a plain PBM (`P1`) image, with every cell as a `scale`×`scale` square. Lit cells are
+/// black, as `1` is black in PBM.
+pub fn write_pbm(mut w: impl Write, pixels: &Grid<bool>, scale: usize) -> io::Result<()> {
+    writeln!(w, "P1")?;
+    writeln!(w, "{} {}", pixels.width() * scale, pixels.height() * scale)?;
+    for row in pixels.rows() {
+        let line: String = row
+            .iter()
+            .flat_map(|&p| std::iter::repeat_n(if p { '1' } else { '0' }, scale))
+            .collect();
+        for _ in 0..scale {
+            writeln!(w, "{line}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Colors of dark and lit cells in GIFs.
+const PALETTE: [[u8; 3]; 2] = [[0x0f, 0x0f, 0x23], [0xff, 0xff, 0x66]];
+/// Two colors only need one bit, but GIF wants LZW codes of at least two.
+const MIN_CODE_SIZE: u8 = 2;
+const MAX_CODE: u16 = 4095;
+
+/// An animated GIF which loops forever. Every frame has to have the same size.
+///
+/// ```no_run
+/// # use aoc2016::Grid;
+/// # use aoc2016::image::Gif;
+/// # use std::time::Duration;
+/// # fn main() -> std::io::Result<()> {
+/// let file = std::fs::File::create("screen.gif")?;
+/// let mut gif = Gif::new(file, 50, 6, 8)?;
+/// gif.frame(&Grid::new(50, 6, false), Duration::from_millis(100))?;
+/// gif.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Gif<W: Write> {
+    w: W,
+    width: usize,
+    height: usize,
+    scale: usize,
+}
+
+impl<W: Write> Gif<W> {
+    /// Writes the header for frames of `width`×`height` cells, each drawn `scale` pixels wide.
+    pub fn new(mut w: W, width: usize, height: usize, scale: usize) -> io::Result<Self> {
+        let (pixel_width, pixel_height) = (dimension(width * scale)?, dimension(height * scale)?);
+        w.write_all(b"GIF89a")?;
+        w.write_all(&pixel_width.to_le_bytes())?;
+        w.write_all(&pixel_height.to_le_bytes())?;
+        // a global color table of 2^(0+1) colors, and background color 0 without aspect ratio
+        w.write_all(&[0x80, 0, 0])?;
+        w.write_all(PALETTE.as_flattened())?;
+        // the NETSCAPE2.0 application extension, repeating 0 (= forever) times
+        w.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+        Ok(Self {
+            w,
+            width,
+            height,
+            scale,
+        })
+    }
+
+    /// Adds a frame shown for `delay`, which GIF stores in hundredths of a second.
+    pub fn frame(&mut self, pixels: &Grid<bool>, delay: Duration) -> io::Result<()> {
+        assert_eq!(
+            [pixels.width(), pixels.height()],
+            [self.width, self.height],
+            "all frames need the same size"
+        );
+        let delay = (delay.as_millis() / 10).min(u16::MAX as u128) as u16;
+        // graphic control extension, only for the delay
+        self.w.write_all(&[0x21, 0xf9, 4, 0])?;
+        self.w.write_all(&delay.to_le_bytes())?;
+        self.w.write_all(&[0, 0])?;
+        // image descriptor covering the whole screen, without a local color table
+        self.w.write_all(&[0x2c, 0, 0, 0, 0])?;
+        self.w
+            .write_all(&dimension(self.width * self.scale)?.to_le_bytes())?;
+        self.w
+            .write_all(&dimension(self.height * self.scale)?.to_le_bytes())?;
+        self.w.write_all(&[0, MIN_CODE_SIZE])?;
+        let scale = self.scale;
+        let indices = pixels.rows().flat_map(|row| {
+            let line = row
+                .iter()
+                .flat_map(move |&p| std::iter::repeat_n(p as u8, scale));
+            std::iter::repeat_n(line, scale).flatten()
+        });
+        for block in lzw_encode(indices).chunks(255) {
+            self.w.write_all(&[block.len() as u8])?;
+            self.w.write_all(block)?;
+        }
+        self.w.write_all(&[0])
+    }
+
+    /// Writes the trailer and returns the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.w.write_all(&[0x3b])?;
+        self.w.flush()?;
+        Ok(self.w)
+    }
+}
+
+fn dimension(pixels: usize) -> io::Result<u16> {
+    u16::try_from(pixels).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{pixels} pixels don't fit into a GIF"),
+        )
+    })
+}
+
+/// Packs codes of growing sizes into bytes, least significant bit first.
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Compresses color indices below `2^MIN_CODE_SIZE` the way GIF wants it.
+fn lzw_encode(indices: impl IntoIterator<Item = u8>) -> Vec<u8> {
+    let clear = 1u16 << MIN_CODE_SIZE;
+    let end = clear + 1;
+    let mut out = BitWriter {
+        bytes: Vec::new(),
+        buffer: 0,
+        bits: 0,
+    };
+    let mut codes = HashMap::new();
+    let mut next_code = end + 1;
+    let mut code_size = MIN_CODE_SIZE + 1;
+    out.write(clear, code_size);
+    let mut indices = indices.into_iter();
+    let Some(first) = indices.next() else {
+        out.write(end, code_size);
+        return out.finish();
+    };
+    let mut prefix = first as u16;
+    for index in indices {
+        if let Some(&code) = codes.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+        out.write(prefix, code_size);
+        if next_code <= MAX_CODE {
+            codes.insert((prefix, index), next_code);
+            next_code += 1;
+            // the decoder adds its entries one code later, so it only needs the wider codes once
+            // the entry after this one exists
+            if next_code == (1 << code_size) + 1 && code_size < 12 {
+                code_size += 1;
+            }
+        } else {
+            out.write(clear, code_size);
+            codes.clear();
+            next_code = end + 1;
+            code_size = MIN_CODE_SIZE + 1;
+        }
+        prefix = index as u16;
+    }
+    out.write(prefix, code_size);
+    // the decoder adds one more entry for the last code, which may need another bit
+    if next_code == 1 << code_size && code_size < 12 {
+        code_size += 1;
+    }
+    out.write(end, code_size);
+    out.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A GIF decoder as described in the specification, for the LZW data only.
+    fn lzw_decode(data: &[u8]) -> Vec<u8> {
+        let clear = 1u16 << MIN_CODE_SIZE;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let reset = |table: &mut Vec<Vec<u8>>| {
+            *table = (0..clear).map(|i| vec![i as u8]).collect();
+            table.extend([vec![], vec![]]);
+        };
+        reset(&mut table);
+        let mut code_size = MIN_CODE_SIZE + 1;
+        let (mut pos, mut out, mut prev): (usize, Vec<u8>, Option<Vec<u8>>) = (0, vec![], None);
+        loop {
+            let code = (0..code_size as usize).fold(0u16, |code, i| {
+                let bit = data[(pos + i) / 8] >> ((pos + i) % 8) & 1;
+                code | (bit as u16) << i
+            });
+            pos += code_size as usize;
+            if code == clear {
+                reset(&mut table);
+                code_size = MIN_CODE_SIZE + 1;
+                prev = None;
+                continue;
+            }
+            if code == clear + 1 {
+                return out;
+            }
+            let entry = match (table.get(code as usize), &prev) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(p)) => [&p[..], &p[..1]].concat(),
+                (None, None) => panic!("unknown first code {code}"),
+            };
+            if let Some(p) = prev
+                && table.len() <= MAX_CODE as usize
+            {
+                table.push([&p[..], &entry[..1]].concat());
+                if table.len() == 1 << code_size && code_size < 12 {
+                    code_size += 1;
+                }
+            }
+            out.extend_from_slice(&entry);
+            prev = Some(entry);
+        }
+    }
+
+    #[test]
+    fn test_lzw() {
+        let inputs: [Vec<u8>; 5] = [
+            vec![],
+            vec![1],
+            vec![0, 1, 0, 1, 0, 1, 1, 1, 1, 0, 0, 0],
+            (0..5000).map(|i| ((i * i) % 7 % 2) as u8).collect(),
+            (0..100_000)
+                .map(|i| ((i / 3) ^ (i / 17) ^ (i / 101)) as u8 & 3)
+                .collect(),
+        ];
+        for input in inputs {
+            assert_eq!(lzw_decode(&lzw_encode(input.iter().copied())), input);
+        }
+    }
+
+    #[test]
+    fn test_pbm() {
+        let pixels = Grid::from_vec(3, vec![true, false, false, false, true, true]);
+        let mut out = Vec::new();
+        write_pbm(&mut out, &pixels, 1).unwrap();
+        assert_eq!(out, b"P1\n3 2\n100\n011\n");
+        out.clear();
+        write_pbm(&mut out, &Grid::from_vec(1, vec![true, false]), 2).unwrap();
+        assert_eq!(out, b"P1\n2 4\n11\n11\n00\n00\n");
+    }
+
+    #[test]
+    fn test_gif() {
+        let pixels = Grid::from_vec(2, vec![true, false, false, true]);
+        let mut gif = Gif::new(Vec::new(), 2, 2, 3).unwrap();
+        gif.frame(&pixels, Duration::from_millis(250)).unwrap();
+        let bytes = gif.finish().unwrap();
+        assert!(bytes.starts_with(b"GIF89a\x06\x00\x06\x00"));
+        assert_eq!(bytes.last(), Some(&0x3b));
+        // graphic control extension with a delay of 25 hundredths
+        let gce = bytes.windows(3).position(|w| w == [0x21, 0xf9, 4]).unwrap();
+        assert_eq!(bytes[gce + 4..gce + 6], [25, 0]);
+        // a single data block follows the image descriptor
+        let data = &bytes[gce + 8 + 10 + 1..];
+        let len = data[0] as usize;
+        assert_eq!(data[len + 1], 0);
+        assert_eq!(
+            lzw_decode(&data[1..=len]),
+            [[1, 1, 1, 0, 0, 0]; 3]
+                .into_iter()
+                .chain([[0, 0, 0, 1, 1, 1]; 3])
+                .flatten()
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,262 @@
+//! Sets of integers stored as sorted, disjoint ranges, for puzzles that deal in huge ranges like
+//! IP blacklists.
+//!
+//! Ranges are inclusive on both ends, so a set can reach up to `T::MAX` without overflowing.
+//! Touching ranges are merged, so `1..=3` and `4..=6` are stored as `1..=6`.
+
+use num_traits::PrimInt;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+#[derive(Clone, Eq, PartialEq, Hash, Default)]
+pub struct IntervalSet<T> {
+    /// Sorted, with gaps of at least one value between consecutive ranges.
+    ranges: Vec<(T, T)>,
+}
+
+impl<T: PrimInt> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
+    /// Number of disjoint ranges, not of values. See [`IntervalSet::len`] for that.
+    pub fn range_count(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of values in the set. `u128` because a full `u64` set has 2⁶⁴ of them, which means
+    /// that it overflows for sets of `u128` or `i128` with 2¹²⁸ values.
+    pub fn len(&self) -> u128 {
+        self.ranges
+            .iter()
+            .map(|&(start, end)| {
+                let diff = match (start.to_i128(), end.to_i128()) {
+                    // the difference always fits into a `u128`, even where it doesn't fit an `i128`
+                    (Some(start), Some(end)) => end.wrapping_sub(start) as u128,
+                    // only `u128` values can be too large for an `i128`
+                    _ => end.to_u128().unwrap() - start.to_u128().unwrap(),
+                };
+                diff + 1
+            })
+            .sum()
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let i = self.ranges.partition_point(|&(_, end)| end < value);
+        self.ranges.get(i).is_some_and(|&(start, _)| start <= value)
+    }
+
+    /// The ranges in ascending order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges.iter().map(|&(start, end)| start..=end)
+    }
+
+    /// Adds all values of `range`, merging it with every range it overlaps or touches.
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        let (start, end) = range.into_inner();
+        if start > end {
+            return;
+        }
+        // first range that isn't completely before `start - 1`
+        let i = self
+            .ranges
+            .partition_point(|&(_, e)| start > T::min_value() && e < start - T::one());
+        // first range that is completely after `end + 1`
+        let j = self
+            .ranges
+            .partition_point(|&(s, _)| end == T::max_value() || s <= end + T::one());
+        let merged = if i < j {
+            (start.min(self.ranges[i].0), end.max(self.ranges[j - 1].1))
+        } else {
+            (start, end)
+        };
+        self.ranges.splice(i..j, [merged]);
+    }
+
+    /// Takes all values of `range` out of the set, splitting ranges where necessary.
+    pub fn remove(&mut self, range: RangeInclusive<T>) {
+        let (start, end) = range.into_inner();
+        if start > end {
+            return;
+        }
+        let i = self.ranges.partition_point(|&(_, e)| e < start);
+        let j = self.ranges.partition_point(|&(s, _)| s <= end);
+        if i >= j {
+            return;
+        }
+        let (first, last) = (self.ranges[i].0, self.ranges[j - 1].1);
+        let left = (first < start).then(|| (first, start - T::one()));
+        let right = (last > end).then(|| (end + T::one(), last));
+        self.ranges.splice(i..j, left.into_iter().chain(right));
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for range in other.iter() {
+            result.insert(range);
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut a, mut b) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+        while let (Some(&&(a_start, a_end)), Some(&&(b_start, b_end))) = (a.peek(), b.peek()) {
+            let (start, end) = (a_start.max(b_start), a_end.min(b_end));
+            if start <= end {
+                ranges.push((start, end));
+            }
+            // whichever ends first can't overlap with anything else of the other set
+            if a_end < b_end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        // both inputs are disjoint and sorted, so the overlaps are as well. They can't touch
+        // either, as that would mean one of the inputs had touching ranges.
+        Self { ranges }
+    }
+
+    /// All values within `bounds` which are not in the set.
+    pub fn complement(&self, bounds: RangeInclusive<T>) -> Self {
+        let mut result = Self::from(bounds);
+        for range in self.iter() {
+            result.remove(range);
+        }
+        result
+    }
+
+    /// The smallest value within `bounds` which is not in the set.
+    pub fn first_gap(&self, bounds: RangeInclusive<T>) -> Option<T> {
+        let (mut candidate, end) = bounds.into_inner();
+        if candidate > end {
+            return None;
+        }
+        let i = self.ranges.partition_point(|&(_, e)| e < candidate);
+        for &(s, e) in &self.ranges[i..] {
+            if s > candidate {
+                break;
+            }
+            if e >= end {
+                return None;
+            }
+            candidate = e + T::one();
+        }
+        Some(candidate)
+    }
+}
+
+impl<T: PrimInt> From<RangeInclusive<T>> for IntervalSet<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        let mut result = Self::new();
+        result.insert(range);
+        result
+    }
+}
+
+impl<T: PrimInt> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        let mut result = Self::new();
+        result.extend(iter);
+        result
+    }
+}
+
+impl<T: PrimInt> Extend<RangeInclusive<T>> for IntervalSet<T> {
+    fn extend<I: IntoIterator<Item = RangeInclusive<T>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for IntervalSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(self.ranges.iter().map(|(start, end)| start..=end))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges<T: PrimInt>(set: &IntervalSet<T>) -> Vec<RangeInclusive<T>> {
+        set.iter().collect()
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut set = IntervalSet::new();
+        set.insert(10..=12);
+        set.insert(1..=3);
+        set.insert(5..=6);
+        assert_eq!(ranges(&set), [1..=3, 5..=6, 10..=12]);
+        set.insert(4..=4);
+        assert_eq!(ranges(&set), [1..=6, 10..=12]);
+        set.insert(8..=20);
+        assert_eq!(ranges(&set), [1..=6, 8..=20]);
+        set.insert(0..=u8::MAX);
+        assert_eq!(ranges(&set), [0..=u8::MAX]);
+        assert_eq!(set.len(), 256);
+        #[allow(clippy::reversed_empty_ranges)]
+        set.remove(3..=2);
+        assert_eq!(set.range_count(), 1);
+
+        let mut set = IntervalSet::new();
+        set.insert(0..=5u128);
+        set.insert(u128::MAX - 9..=u128::MAX);
+        assert_eq!(ranges(&set), [0..=5, u128::MAX - 9..=u128::MAX]);
+        assert_eq!(set.len(), 16);
+        let set = IntervalSet::from(i128::MIN..=i128::MAX - 1);
+        assert_eq!(set.len(), u128::MAX);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set = IntervalSet::from(0..=u32::MAX);
+        set.remove(5..=8);
+        assert_eq!(ranges(&set), [0..=4, 9..=u32::MAX]);
+        set.remove(0..=2);
+        set.remove(4..=10);
+        assert_eq!(ranges(&set), [3..=3, 11..=u32::MAX]);
+        set.remove(20..=u32::MAX);
+        assert_eq!(ranges(&set), [3..=3, 11..=19]);
+        assert_eq!(set.len(), 10);
+        assert!(set.contains(3) && set.contains(19));
+        assert!(!set.contains(4) && !set.contains(20));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: IntervalSet<i32> = [-5..=0, 3..=7, 10..=10].into_iter().collect();
+        let b: IntervalSet<i32> = [-2..=4, 6..=12].into_iter().collect();
+        assert_eq!(ranges(&a.union(&b)), [-5..=12]);
+        assert_eq!(ranges(&a.intersection(&b)), [-2..=0, 3..=4, 6..=7, 10..=10]);
+        assert_eq!(ranges(&a.complement(-10..=10)), [-10..=-6, 1..=2, 8..=9]);
+        assert_eq!(a.first_gap(-5..=20), Some(1));
+        assert_eq!(a.first_gap(-7..=20), Some(-7));
+        assert_eq!(a.first_gap(3..=7), None);
+        assert_eq!(
+            IntervalSet::from(i8::MIN..=i8::MAX).first_gap(0..=i8::MAX),
+            None
+        );
+    }
+
+    #[test]
+    fn test_blacklist() {
+        // the example of 2016 day 20
+        let blacklist: IntervalSet<u32> = [5..=8, 0..=2, 4..=7].into_iter().collect();
+        assert_eq!(blacklist.first_gap(0..=9), Some(3));
+        assert_eq!(blacklist.complement(0..=9).len(), 2);
+    }
+}
@@ -0,0 +1,325 @@
+//! Who is left over when `n` people in a circle take turns eliminating each other, as in 2016's
+//! elf white elephant party. Positions are 0-based, and the person at `0` goes first.
+//!
+//! Both variants have a closed form taking O(log n). The O(n) simulations are there to check
+//! them against. Other rules can be played out with [`Game`], in O(n log n).
+
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Largest power of `base` that is at most `n`. `n` has to be positive.
+fn largest_power_below(n: u64, base: u64) -> u64 {
+    let mut p = 1;
+    while p <= n / base {
+        p *= base;
+    }
+    p
+}
+
+/// Everyone eliminates the next person to their left, which is the classic Josephus problem with
+/// every second person removed: with `n = 2^m + l`, the winner is `2l`.
+pub fn steal_from_left(n: u64) -> u64 {
+    assert_ne!(n, 0, "there has to be someone in the circle");
+    2 * (n - largest_power_below(n, 2))
+}
+
+/// Everyone eliminates the person straight across, or the left one of the two across if the
+/// circle has an even size. With `3^m` the largest power of three up to `n`, the winner walks
+/// up one step at a time until `n = 2 * 3^m`, and two steps at a time after that.
+pub fn steal_from_across(n: u64) -> u64 {
+    assert_ne!(n, 0, "there has to be someone in the circle");
+    let p = largest_power_below(n, 3);
+    if n == p {
+        n - 1
+    } else if n - p <= p {
+        n - p - 1
+    } else {
+        2 * n - 3 * p - 1
+    }
+}
+
+/// [`steal_from_left`] by playing it out.
+pub fn simulate_left(n: usize) -> usize {
+    assert_ne!(n, 0, "there has to be someone in the circle");
+    let mut circle: VecDeque<usize> = (0..n).collect();
+    while circle.len() > 1 {
+        let thief = circle.pop_front().unwrap();
+        circle.pop_front();
+        circle.push_back(thief);
+    }
+    circle[0]
+}
+
+/// [`steal_from_across`] by playing it out. The circle is split into two halves with the current
+/// player at the front of `near` and the one across at the front of `far`, so every step only
+/// touches the ends of the two queues.
+pub fn simulate_across(n: usize) -> usize {
+    assert_ne!(n, 0, "there has to be someone in the circle");
+    let mut near: VecDeque<usize> = (0..n / 2).collect();
+    let mut far: VecDeque<usize> = (n / 2..n).collect();
+    while !near.is_empty() {
+        // the one across is at the front of `far`. `far` is never shorter than `near`.
+        far.pop_front();
+        // the current player moves to the back of the circle, which is the back of `far`.
+        let player = near.pop_front().unwrap();
+        far.push_back(player);
+        // restore the balance, so that the front of `far` is across again.
+        if far.len() > near.len() + 1 {
+            near.push_back(far.pop_front().unwrap());
+        }
+    }
+    far[0]
+}
+
+/// Who a player steals from, as an offset to the left among the players still in the circle.
+#[derive(Copy, Clone)]
+pub enum Target<'a> {
+    /// The next player, which is [`steal_from_left`].
+    Left,
+    /// The one straight across, which is [`steal_from_across`].
+    Across,
+    /// Passes over `k` other players and steals from the one after them, going around the circle
+    /// as often as needed. `Skip(0)` is [`Target::Left`].
+    Skip(usize),
+    /// The offset for a circle of `n` players. It mustn't be a multiple of `n`, as players can't
+    /// steal from themselves.
+    Offset(&'a dyn Fn(usize) -> usize),
+}
+
+impl Target<'_> {
+    fn offset(&self, n: usize) -> usize {
+        match self {
+            Target::Left => 1,
+            Target::Across => n / 2,
+            Target::Skip(k) => k % (n - 1) + 1,
+            Target::Offset(f) => f(n),
+        }
+    }
+}
+
+impl fmt::Debug for Target<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Left => write!(f, "Left"),
+            Target::Across => write!(f, "Across"),
+            Target::Skip(k) => write!(f, "Skip({k})"),
+            Target::Offset(_) => write!(f, "Offset(..)"),
+        }
+    }
+}
+
+/// How many of the victim's presents the thief gets. The victim leaves the circle either way, and
+/// whatever the thief doesn't take is lost.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Share {
+    All,
+    /// `numerator / denominator` of the presents, rounded up so that there is always something to
+    /// take. The fraction has to be positive and at most one.
+    Fraction(u64, u64),
+}
+
+impl Share {
+    fn of(self, presents: u64) -> u64 {
+        match self {
+            Share::All => presents,
+            // never more than `presents`, but the product may not fit into a u64
+            Share::Fraction(numerator, denominator) => {
+                (presents as u128 * numerator as u128).div_ceil(denominator as u128) as u64
+            }
+        }
+    }
+}
+
+/// A circle of players with one present each. Starting at `0`, each player in turn steals from
+/// their [`Target`], who then leaves the circle, and the turn passes on to the next player to the
+/// left of the thief.
+#[derive(Copy, Clone, Debug)]
+pub struct Game<'a> {
+    target: Target<'a>,
+    share: Share,
+    record_order: bool,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Outcome {
+    pub winner: usize,
+    /// The presents the winner ends up with.
+    pub presents: u64,
+    /// Everyone but the winner, in the order they left the circle. Only there if asked for with
+    /// [`Game::record_order`].
+    pub order: Option<Vec<usize>>,
+}
+
+impl<'a> Game<'a> {
+    pub fn new(target: Target<'a>) -> Self {
+        Self {
+            target,
+            share: Share::All,
+            record_order: false,
+        }
+    }
+
+    pub fn share(mut self, share: Share) -> Self {
+        if let Share::Fraction(numerator, denominator) = share {
+            assert!(
+                0 < numerator && numerator <= denominator,
+                "{numerator}/{denominator} isn't a fraction of the presents"
+            );
+        }
+        self.share = share;
+        self
+    }
+
+    /// Also collects the order in which players leave the circle.
+    pub fn record_order(mut self) -> Self {
+        self.record_order = true;
+        self
+    }
+
+    pub fn play(&self, n: usize) -> Outcome {
+        assert_ne!(n, 0, "there has to be someone in the circle");
+        let mut circle = RankedSet::full(n);
+        let mut presents = vec![1u64; n];
+        let mut order = self.record_order.then(|| Vec::with_capacity(n - 1));
+        let mut current = 0;
+        for remaining in (2..=n).rev() {
+            let offset = self.target.offset(remaining) % remaining;
+            assert_ne!(offset, 0, "player {current} would steal from themselves");
+            let rank = circle.rank(current);
+            let victim = circle.select((rank + offset) % remaining);
+            presents[current] += self.share.of(presents[victim]);
+            presents[victim] = 0;
+            circle.remove(victim);
+            if let Some(order) = &mut order {
+                order.push(victim);
+            }
+            // the thief is still there, so the next one has the rank after theirs.
+            current = circle.select((circle.rank(current) + 1) % (remaining - 1));
+        }
+        Outcome {
+            winner: current,
+            presents: presents[current],
+            order,
+        }
+    }
+}
+
+/// The players still in the circle, as a Fenwick tree over `0` for gone and `1` for present.
+/// Both looking up the rank of a player and the player at a rank take O(log n).
+struct RankedSet {
+    tree: Vec<u32>,
+}
+
+impl RankedSet {
+    fn full(n: usize) -> Self {
+        // tree[i] covers the `i & -i` values ending at i, 1-based
+        let tree = (0..=n).map(|i| (i & i.wrapping_neg()) as u32).collect();
+        Self { tree }
+    }
+
+    fn remove(&mut self, value: usize) {
+        let mut i = value + 1;
+        while i < self.tree.len() {
+            self.tree[i] -= 1;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// How many values smaller than `value` are in the set.
+    fn rank(&self, value: usize) -> usize {
+        let mut i = value;
+        let mut count = 0;
+        while i > 0 {
+            count += self.tree[i] as usize;
+            i &= i - 1;
+        }
+        count
+    }
+
+    /// The value with `rank` smaller values in the set.
+    fn select(&self, mut rank: usize) -> usize {
+        let mut pos = 0;
+        let mut step = (self.tree.len() - 1).checked_ilog2().map_or(0, |b| 1 << b);
+        while step > 0 {
+            if pos + step < self.tree.len() && (self.tree[pos + step] as usize) <= rank {
+                pos += step;
+                rank -= self.tree[pos] as usize;
+            }
+            step >>= 1;
+        }
+        // `pos` values before it are all either gone or ranked lower, so it's the next one.
+        pos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        assert_eq!(steal_from_left(5), 2);
+        assert_eq!(steal_from_across(5), 1);
+        assert_eq!(simulate_left(5), 2);
+        assert_eq!(simulate_across(5), 1);
+    }
+
+    #[test]
+    fn test_closed_forms() {
+        for n in 1..=3000 {
+            assert_eq!(
+                steal_from_left(n as u64),
+                simulate_left(n) as u64,
+                "n = {n}"
+            );
+            assert_eq!(
+                steal_from_across(n as u64),
+                simulate_across(n) as u64,
+                "n = {n}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_game() {
+        for n in 1..=500 {
+            let left = Game::new(Target::Left).play(n);
+            assert_eq!(left.winner as u64, steal_from_left(n as u64), "n = {n}");
+            assert_eq!(left.presents, n as u64);
+            let across = Game::new(Target::Across).play(n);
+            assert_eq!(across.winner as u64, steal_from_across(n as u64), "n = {n}");
+            assert_eq!(Game::new(Target::Skip(0)).play(n), left);
+        }
+    }
+
+    #[test]
+    fn test_game_variants() {
+        let outcome = Game::new(Target::Left).record_order().play(5);
+        assert_eq!(outcome.order, Some(vec![1, 3, 0, 4]));
+        assert_eq!(outcome.winner, 2);
+
+        // 0 steals from 2, 1 from 4 and 3 from 1, and then 0 from 3
+        let outcome = Game::new(Target::Skip(1)).record_order().play(5);
+        assert_eq!(outcome.order, Some(vec![2, 4, 1, 3]));
+        assert_eq!(outcome.winner, 0);
+
+        // steal from the one to the right, while the turn still passes to the left
+        let right = |n| n - 1;
+        let outcome = Game::new(Target::Offset(&right)).record_order().play(4);
+        assert_eq!(outcome.order, Some(vec![3, 0, 1]));
+        assert_eq!(outcome.winner, 2);
+
+        // 0 and 2 take 1 present each from 1 and 3, then 4 and 2 take 1 of 2 from 0 and 4
+        let outcome = Game::new(Target::Left).share(Share::Fraction(1, 2)).play(5);
+        assert_eq!(outcome.winner, 2);
+        assert_eq!(outcome.presents, 3);
+    }
+
+    #[test]
+    fn test_share() {
+        assert_eq!(Share::All.of(7), 7);
+        assert_eq!(Share::Fraction(1, 3).of(7), 3);
+        assert_eq!(Share::Fraction(2, 3).of(u64::MAX), u64::MAX / 3 * 2);
+        assert_eq!(Share::Fraction(u64::MAX, u64::MAX).of(u64::MAX), u64::MAX);
+    }
+}
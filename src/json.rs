@@ -0,0 +1,150 @@
+//! Just enough JSON to hand results to other tools, without pulling in a serialization framework.
+//!
+//! Values are built from Rust values with [`From`] and [`Json::object`], and written compactly with
+//! [`Display`].
+
+use std::fmt;
+use std::fmt::{Display, Formatter, Write};
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub enum Json {
+    #[default]
+    Null,
+    Bool(bool),
+    /// Wide enough for nanosecond timings and every answer type used so far.
+    Int(i128),
+    String(String),
+    Array(Vec<Json>),
+    /// Keys keep the order they were given in.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object<K: Into<String>>(fields: impl IntoIterator<Item = (K, Json)>) -> Self {
+        Json::Object(fields.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+
+    pub fn array<T: Into<Json>>(items: impl IntoIterator<Item = T>) -> Self {
+        Json::Array(items.into_iter().map(Into::into).collect())
+    }
+}
+
+macro_rules! from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Json {
+                fn from(v: $t) -> Self {
+                    Json::Int(v as i128)
+                }
+            }
+        )*
+    };
+}
+
+from_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl From<u128> for Json {
+    /// Saturates at `i128::MAX`, which no timing will ever reach.
+    fn from(v: u128) -> Self {
+        Json::Int(v.try_into().unwrap_or(i128::MAX))
+    }
+}
+
+impl From<bool> for Json {
+    fn from(v: bool) -> Self {
+        Json::Bool(v)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(v: &str) -> Self {
+        Json::String(v.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(v: String) -> Self {
+        Json::String(v)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(v: Vec<T>) -> Self {
+        Json::array(v)
+    }
+}
+
+fn write_string(f: &mut Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::Int(i) => write!(f, "{i}"),
+            Json::String(s) => write_string(f, s),
+            Json::Array(items) => {
+                f.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                f.write_char(']')
+            }
+            Json::Object(fields) => {
+                f.write_char('{')?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ": {value}")?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let json = Json::object([
+            ("day", Json::from(8)),
+            ("answer", Json::from("#.\n.#")),
+            ("quote", Json::from("say \"hi\"\\\u{1}")),
+            ("parts", Json::array([1u8, 2])),
+            ("debug", Json::from(None::<u32>)),
+            ("ok", Json::from(true)),
+        ]);
+        assert_eq!(
+            json.to_string(),
+            r##"{"day": 8, "answer": "#.\n.#", "quote": "say \"hi\"\\\u0001", "parts": [1, 2], "debug": null, "ok": true}"##
+        );
+    }
+}
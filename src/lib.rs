@@ -2,36 +2,161 @@ extern crate core;
 
 pub const ALPHABET_COUNT: usize = (b'z' - b'a') as usize + 1;
 
-pub use alphabet_map::AlphabetMap;
-pub use index_map::IndexMap;
-pub use lin_alg::SymmetricalMatrix;
-pub use vec_math_ext::vec2_hamming_dist;
+pub use alphabet_map::{Alphabet, AlphabetMap};
+pub use direction::{Direction, Turn, Turtle};
+pub use grid::Grid;
+pub use index_map::{IndexKey, IndexMap};
+pub use interval_set::IntervalSet;
+pub use json::Json;
+pub use lin_alg::{DistanceMatrix, Symmetrical, SymmetricalMatrix};
+pub use parse::ParseError;
+pub use solution::Solution;
+pub use vec_math::{Vec2, Vec3};
+
+/// Implements [`IndexKey`] for a tuple newtype around an integer, e.g. `struct BotId(u32);`.
+#[macro_export]
+macro_rules! index_key {
+    ($t:ty) => {
+        impl $crate::IndexKey for $t {
+            #[inline]
+            fn to_index(&self) -> usize {
+                $crate::IndexKey::to_index(&self.0)
+            }
+
+            #[inline]
+            fn from_index(index: usize) -> Self {
+                Self($crate::IndexKey::from_index(index))
+            }
+        }
+    };
+}
 
+pub mod bench;
+pub mod direction;
 pub mod graph;
+pub mod grid;
+pub mod image;
+pub mod interval_set;
+pub mod josephus;
+pub mod json;
+pub mod number_theory;
+pub mod ocr;
+pub mod parse;
+pub mod progress;
+pub mod solution;
+pub mod vec_math;
 
 pub mod alphabet_map {
     use crate::ALPHABET_COUNT;
+    use std::cmp::Reverse;
     use std::iter::{IntoIterator, Iterator};
+    use std::marker::PhantomData;
     use std::ops::{Index, IndexMut};
 
     type Entry<Value> = (char, Value);
-    type Entries<Value> = [Entry<Value>; ALPHABET_COUNT];
+    type Entries<Value, const N: usize> = [Entry<Value>; N];
+
+    /// A fixed set of `N` characters, which are the keys of an [`AlphabetMap`].
+    /// The order of [`Alphabet::CHARS`] is the order used for iteration and tie-breaking.
+    pub trait Alphabet<const N: usize> {
+        const CHARS: [char; N];
+
+        #[inline]
+        fn index_of(c: char) -> Option<usize> {
+            Self::CHARS.iter().position(|&x| x == c)
+        }
+    }
+
+    const fn ascii_range<const N: usize>(first: u8) -> [char; N] {
+        let mut result = ['\0'; N];
+        let mut i = 0;
+        while i < N {
+            result[i] = (first + i as u8) as char;
+            i += 1;
+        }
+        result
+    }
+
+    /// `a..=z`
+    #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+    pub struct Lowercase;
+
+    /// `A..=Z`
+    #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+    pub struct Uppercase;
+
+    /// `0..=9`
+    #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+    pub struct Digits;
+
+    /// `0..=9` followed by `a..=f`
+    #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+    pub struct HexDigits;
+
+    impl Alphabet<ALPHABET_COUNT> for Lowercase {
+        const CHARS: [char; ALPHABET_COUNT] = ascii_range(b'a');
+
+        #[inline]
+        fn index_of(c: char) -> Option<usize> {
+            c.is_ascii_lowercase().then(|| (c as u8 - b'a') as usize)
+        }
+    }
+
+    impl Alphabet<ALPHABET_COUNT> for Uppercase {
+        const CHARS: [char; ALPHABET_COUNT] = ascii_range(b'A');
+
+        #[inline]
+        fn index_of(c: char) -> Option<usize> {
+            c.is_ascii_uppercase().then(|| (c as u8 - b'A') as usize)
+        }
+    }
+
+    impl Alphabet<10> for Digits {
+        const CHARS: [char; 10] = ascii_range(b'0');
+
+        #[inline]
+        fn index_of(c: char) -> Option<usize> {
+            c.to_digit(10).map(|d| d as usize)
+        }
+    }
+
+    impl Alphabet<16> for HexDigits {
+        const CHARS: [char; 16] = [
+            '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f',
+        ];
+
+        #[inline]
+        fn index_of(c: char) -> Option<usize> {
+            match c {
+                '0'..='9' | 'a'..='f' => c.to_digit(16).map(|d| d as usize),
+                _ => None,
+            }
+        }
+    }
+
+    pub type UppercaseMap<Value> = AlphabetMap<Value, Uppercase, ALPHABET_COUNT>;
+    pub type DigitMap<Value> = AlphabetMap<Value, Digits, 10>;
+    pub type HexDigitMap<Value> = AlphabetMap<Value, HexDigits, 16>;
 
     #[derive(Clone, Eq, PartialEq, Debug)]
-    pub struct AlphabetMap<Value> {
-        entries: Entries<Value>,
+    pub struct AlphabetMap<Value, A: Alphabet<N> = Lowercase, const N: usize = ALPHABET_COUNT> {
+        entries: Entries<Value, N>,
+        alphabet: PhantomData<A>,
     }
 
-    impl<Value> IntoIterator for AlphabetMap<Value> {
+    impl<Value, A: Alphabet<N>, const N: usize> IntoIterator for AlphabetMap<Value, A, N> {
         type Item = Entry<Value>;
-        type IntoIter = <Entries<Value> as IntoIterator>::IntoIter;
+        type IntoIter = <Entries<Value, N> as IntoIterator>::IntoIter;
 
         fn into_iter(self) -> Self::IntoIter {
             self.entries.into_iter()
         }
     }
 
-    impl<Value: Default> FromIterator<Entry<Value>> for AlphabetMap<Value> {
+    /// panics on keys that are not part of the alphabet.
+    impl<Value: Default, A: Alphabet<N>, const N: usize> FromIterator<Entry<Value>>
+        for AlphabetMap<Value, A, N>
+    {
         fn from_iter<T: IntoIterator<Item = Entry<Value>>>(iter: T) -> Self {
             let mut result = Self::new();
             for (k, v) in iter {
@@ -41,23 +166,60 @@ pub mod alphabet_map {
         }
     }
 
-    impl<Value: Default> AlphabetMap<Value> {
+    impl<Value: Default, A: Alphabet<N>, const N: usize> AlphabetMap<Value, A, N> {
         pub fn new() -> Self {
-            let mut entries = Entries::<Value>::default();
-            for (i, entry) in entries.iter_mut().enumerate().take(ALPHABET_COUNT) {
-                entry.0 = (b'a' + i as u8) as char
+            Self {
+                entries: A::CHARS.map(|c| (c, Value::default())),
+                alphabet: PhantomData,
             }
-            Self { entries }
         }
     }
 
-    impl<Value: Default> Default for AlphabetMap<Value> {
+    impl<Value: Default, A: Alphabet<N>, const N: usize> Default for AlphabetMap<Value, A, N> {
         fn default() -> Self {
             Self::new()
         }
     }
 
-    impl<Value> AlphabetMap<Value> {
+    impl<A: Alphabet<N>, const N: usize> AlphabetMap<usize, A, N> {
+        /// Counts the occurrences of every character. Characters outside the alphabet are ignored.
+        pub fn histogram(chars: impl IntoIterator<Item = char>) -> Self {
+            let mut result = Self::new();
+            for c in chars {
+                if let Some(count) = result.get_mut(c) {
+                    *count += 1;
+                }
+            }
+            result
+        }
+
+        /// Like [`AlphabetMap::least_common`], but only considers characters that occurred at least
+        /// once.
+        pub fn least_common_present(&self) -> Option<(char, usize)> {
+            self.entries
+                .iter()
+                .filter(|(_, count)| *count != 0)
+                .min_by_key(|(_, count)| *count)
+                .map(|&(c, count)| (c, count))
+        }
+    }
+
+    impl<Value, A: Alphabet<N>, const N: usize> AlphabetMap<Value, A, N> {
+        #[inline]
+        pub fn get(&self, key: char) -> Option<&Value> {
+            A::index_of(key).map(|i| &self.entries[i].1)
+        }
+
+        #[inline]
+        pub fn get_mut(&mut self, key: char) -> Option<&mut Value> {
+            A::index_of(key).map(|i| &mut self.entries[i].1)
+        }
+
+        #[inline]
+        pub fn contains_key(&self, key: char) -> bool {
+            A::index_of(key).is_some()
+        }
+
         pub fn iter(&self) -> impl Iterator<Item = &'_ Entry<Value>> {
             self.entries.iter()
         }
@@ -75,39 +237,71 @@ pub mod alphabet_map {
         }
     }
 
-    impl<Value> AlphabetMap<Value> {
-        pub fn from_entries_unchecked(value: Entries<Value>) -> Self {
-            AlphabetMap { entries: value }
+    impl<Value: Ord, A: Alphabet<N>, const N: usize> AlphabetMap<Value, A, N> {
+        /// The entry with the largest value. Ties are won by the character that comes first in
+        /// the alphabet.
+        pub fn most_common(&self) -> Option<(char, &Value)> {
+            self.entries
+                .iter()
+                .rev()
+                .max_by_key(|(_, v)| v)
+                .map(|(c, v)| (*c, v))
+        }
+
+        /// The entry with the smallest value. Ties are won by the character that comes first in
+        /// the alphabet.
+        pub fn least_common(&self) -> Option<(char, &Value)> {
+            self.entries
+                .iter()
+                .min_by_key(|(_, v)| v)
+                .map(|(c, v)| (*c, v))
+        }
+
+        /// All entries, largest value first. Ties are in alphabet order.
+        pub fn by_frequency(&self) -> impl Iterator<Item = (char, &Value)> {
+            let mut entries: Vec<_> = self.entries.iter().map(|(c, v)| (*c, v)).collect();
+            // sort_by_key is stable, so equal values keep their alphabet order.
+            entries.sort_by_key(|&(_, v)| Reverse(v));
+            entries.into_iter()
         }
     }
 
-    impl<Value: Default, const N: usize> From<[(char, Value); N]> for AlphabetMap<Value> {
-        fn from(value: [(char, Value); N]) -> Self {
+    impl<Value, A: Alphabet<N>, const N: usize> AlphabetMap<Value, A, N> {
+        pub fn from_entries_unchecked(value: Entries<Value, N>) -> Self {
+            AlphabetMap {
+                entries: value,
+                alphabet: PhantomData,
+            }
+        }
+    }
+
+    impl<Value: Default, A: Alphabet<N>, const N: usize, const M: usize> From<[(char, Value); M]>
+        for AlphabetMap<Value, A, N>
+    {
+        fn from(value: [(char, Value); M]) -> Self {
             Self::from_iter(value)
         }
     }
 
-    impl<Value> From<AlphabetMap<Value>> for Entries<Value> {
-        fn from(value: AlphabetMap<Value>) -> Self {
+    impl<Value, A: Alphabet<N>, const N: usize> From<AlphabetMap<Value, A, N>> for Entries<Value, N> {
+        fn from(value: AlphabetMap<Value, A, N>) -> Self {
             value.entries
         }
     }
 
-    impl<Value> Index<char> for AlphabetMap<Value> {
+    impl<Value, A: Alphabet<N>, const N: usize> Index<char> for AlphabetMap<Value, A, N> {
         type Output = Value;
 
         fn index(&self, index: char) -> &Self::Output {
-            assert!(index >= 'a');
-            assert!(index <= 'z');
-            &self.entries[(index as u8 - b'a') as usize].1
+            self.get(index)
+                .unwrap_or_else(|| panic!("'{index}' is not part of the alphabet"))
         }
     }
 
-    impl<Value> IndexMut<char> for AlphabetMap<Value> {
+    impl<Value, A: Alphabet<N>, const N: usize> IndexMut<char> for AlphabetMap<Value, A, N> {
         fn index_mut(&mut self, index: char) -> &mut Self::Output {
-            assert!(index >= 'a');
-            assert!(index <= 'z');
-            &mut self.entries[(index as u8 - b'a') as usize].1
+            self.get_mut(index)
+                .unwrap_or_else(|| panic!("'{index}' is not part of the alphabet"))
         }
     }
 
@@ -126,34 +320,91 @@ pub mod alphabet_map {
             assert_eq!(m[&'b'], 7);
             assert_eq!(m[&'x'], 5);
         }
+
+        #[test]
+        fn test_get() {
+            let mut am = AlphabetMap::<u8>::new();
+            assert_eq!(am.get('A'), None);
+            assert_eq!(am.get('1'), None);
+            *am.get_mut('q').unwrap() = 3;
+            assert_eq!(am.get('q'), Some(&3));
+        }
+
+        #[test]
+        fn test_histogram() {
+            let am = AlphabetMap::<usize>::histogram("not-a-real-room".chars());
+            assert_eq!(am['o'], 3);
+            assert_eq!(am.most_common(), Some(('o', &3)));
+            assert_eq!(am.least_common(), Some(('b', &0)));
+            assert_eq!(am.least_common_present(), Some(('e', 1)));
+            assert_eq!(
+                am.by_frequency()
+                    .take(5)
+                    .map(|(c, _)| c)
+                    .collect::<String>(),
+                "oarel"
+            );
+        }
+
+        #[test]
+        fn test_other_alphabets() {
+            let hex = HexDigitMap::<usize>::histogram("deadbeef 42".chars());
+            assert_eq!(hex['e'], 3);
+            assert_eq!(hex.get('g'), None);
+            assert_eq!(hex.most_common(), Some(('e', &3)));
+            let digits = DigitMap::<usize>::histogram("3018458".chars());
+            assert_eq!(digits.most_common(), Some(('8', &2)));
+            let upper = UppercaseMap::<usize>::histogram("ABBA".chars());
+            assert_eq!(upper.by_frequency().next(), Some(('A', &2)));
+        }
     }
 }
 
 pub mod index_map {
-    #![allow(private_bounds)]
-
-    use std::error::Error;
     use std::fmt::{Debug, Formatter};
     use std::marker::PhantomData;
-    use std::ops::Index;
+    use std::ops::{Index, IndexMut};
 
-    trait Key:
-        From<u8> + Default + TryFrom<usize, Error: Error> + TryInto<usize, Error: Error>
-    {
+    /// Keys of an [`IndexMap`] are dense indices into its storage. This is implemented for all
+    /// primitive integers, and [`index_key!`](crate::index_key) implements it for newtypes
+    /// around them.
+    pub trait IndexKey {
+        /// panics if the key can't be used as an index, for example if it is negative.
+        fn to_index(&self) -> usize;
+
+        fn from_index(index: usize) -> Self;
     }
-    impl<T: From<u8> + Default + TryFrom<usize, Error: Error> + TryInto<usize, Error: Error>> Key
-        for T
-    {
+
+    macro_rules! impl_index_key_for_primitive {
+        ($($t:ty),+) => {
+            $(
+                impl IndexKey for $t {
+                    #[inline]
+                    fn to_index(&self) -> usize {
+                        (*self).try_into().unwrap()
+                    }
+
+                    #[inline]
+                    fn from_index(index: usize) -> Self {
+                        index.try_into().unwrap()
+                    }
+                }
+            )+
+        };
     }
 
+    impl_index_key_for_primitive!(
+        u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+    );
+
     #[derive(Eq, PartialEq)]
-    pub struct IndexMap<K: Key, V> {
+    pub struct IndexMap<K: IndexKey, V> {
         data: Vec<Option<V>>,
         len: usize,
         key_pd: PhantomData<K>,
     }
 
-    impl<K: Key + Clone, V: Clone> Clone for IndexMap<K, V> {
+    impl<K: IndexKey + Clone, V: Clone> Clone for IndexMap<K, V> {
         fn clone(&self) -> Self {
             Self {
                 data: Clone::clone(&self.data),
@@ -163,7 +414,7 @@ pub mod index_map {
         }
     }
 
-    impl<K: Key, V> Default for IndexMap<K, V> {
+    impl<K: IndexKey, V> Default for IndexMap<K, V> {
         fn default() -> Self {
             Self {
                 data: Default::default(),
@@ -173,32 +424,76 @@ pub mod index_map {
         }
     }
 
-    impl<K: Key + Debug, V: Debug> Debug for IndexMap<K, V> {
+    impl<K: IndexKey + Debug, V: Debug> Debug for IndexMap<K, V> {
         fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
             f.debug_map().entries(self.iter()).finish()
         }
     }
 
-    impl<K: Key, V> IndexMap<K, V> {
+    impl<K: IndexKey, V> IndexMap<K, V> {
         #[inline]
         #[must_use]
         pub fn new() -> Self {
             IndexMap::<K, V>::default()
         }
 
-        pub fn insert(&mut self, k: K, v: V) {
-            let k: usize = k.try_into().unwrap();
+        /// Reserves room for keys up to (excluding) `capacity` without reallocating.
+        #[inline]
+        #[must_use]
+        pub fn with_capacity(capacity: usize) -> Self {
+            Self {
+                data: Vec::with_capacity(capacity),
+                ..Self::default()
+            }
+        }
+
+        /// Returns the previous value, if there was one.
+        pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+            let k = k.to_index();
             self.ensure_capacity(k + 1);
-            self.data[k] = Some(v);
+            let old = self.data[k].replace(v);
+            if old.is_none() {
+                self.len += 1;
+            }
+            old
+        }
+
+        /// Removes the value at `k`. Trailing empty slots are dropped, so the storage shrinks
+        /// back down when the largest keys are removed.
+        pub fn remove(&mut self, k: K) -> Option<V> {
+            let k = k.to_index();
+            let old = self.data.get_mut(k)?.take()?;
+            self.len -= 1;
+            let new_len = self
+                .data
+                .iter()
+                .rposition(Option::is_some)
+                .map_or(0, |i| i + 1);
+            self.data.truncate(new_len);
+            Some(old)
+        }
+
+        pub fn clear(&mut self) {
+            self.data.clear();
+            self.len = 0;
+        }
+
+        pub fn entry(&mut self, k: K) -> Entry<'_, K, V> {
+            let index = k.to_index();
+            if self.data.get(index).is_some_and(Option::is_some) {
+                Entry::Occupied(OccupiedEntry { map: self, index })
+            } else {
+                Entry::Vacant(VacantEntry { map: self, index })
+            }
         }
 
         pub fn contains(&self, k: K) -> bool {
-            let k: usize = k.try_into().unwrap();
+            let k = k.to_index();
             self.data.len() > k && self.data[k].is_some()
         }
 
         pub fn get(&self, k: K) -> Option<&V> {
-            let k: usize = k.try_into().unwrap();
+            let k = k.to_index();
             if self.data.len() > k {
                 self.data[k].as_ref()
             } else {
@@ -207,7 +502,7 @@ pub mod index_map {
         }
 
         pub fn get_mut(&mut self, k: K) -> Option<&mut V> {
-            let k: usize = k.try_into().unwrap();
+            let k = k.to_index();
             if self.data.len() > k {
                 self.data[k].as_mut()
             } else {
@@ -217,7 +512,7 @@ pub mod index_map {
 
         #[inline]
         pub fn len(&self) -> usize {
-            self.data.iter().flatten().count()
+            self.len
         }
 
         #[inline]
@@ -230,6 +525,16 @@ pub mod index_map {
             Iter(self.data.iter(), 0, PhantomData)
         }
 
+        #[inline]
+        pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+            IterMut(self.data.iter_mut(), 0, PhantomData)
+        }
+
+        #[inline]
+        pub fn keys(&self) -> impl Iterator<Item = K> {
+            self.iter().map(|(k, _)| k)
+        }
+
         #[inline]
         pub fn values(&self) -> impl Iterator<Item = &V> {
             self.data.iter().flatten()
@@ -253,7 +558,7 @@ pub mod index_map {
         }
     }
 
-    impl<K: Key, V> Index<K> for IndexMap<K, V> {
+    impl<K: IndexKey, V> Index<K> for IndexMap<K, V> {
         type Output = V;
 
         fn index(&self, index: K) -> &Self::Output {
@@ -261,13 +566,114 @@ pub mod index_map {
         }
     }
 
-    pub struct IntoIter<K: Key, V>(
+    impl<K: IndexKey, V> IndexMut<K> for IndexMap<K, V> {
+        fn index_mut(&mut self, index: K) -> &mut Self::Output {
+            self.get_mut(index).unwrap()
+        }
+    }
+
+    impl<K: IndexKey, V> FromIterator<(K, V)> for IndexMap<K, V> {
+        fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+            let mut result = Self::new();
+            result.extend(iter);
+            result
+        }
+    }
+
+    impl<K: IndexKey, V> Extend<(K, V)> for IndexMap<K, V> {
+        fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+            for (k, v) in iter {
+                self.insert(k, v);
+            }
+        }
+    }
+
+    /// A view into a single slot of an [`IndexMap`], see [`IndexMap::entry`].
+    pub enum Entry<'a, K: IndexKey, V> {
+        Occupied(OccupiedEntry<'a, K, V>),
+        Vacant(VacantEntry<'a, K, V>),
+    }
+
+    pub struct OccupiedEntry<'a, K: IndexKey, V> {
+        map: &'a mut IndexMap<K, V>,
+        index: usize,
+    }
+
+    pub struct VacantEntry<'a, K: IndexKey, V> {
+        map: &'a mut IndexMap<K, V>,
+        index: usize,
+    }
+
+    impl<'a, K: IndexKey, V> Entry<'a, K, V> {
+        pub fn key(&self) -> K {
+            let (Entry::Occupied(OccupiedEntry { index, .. })
+            | Entry::Vacant(VacantEntry { index, .. })) = self;
+            K::from_index(*index)
+        }
+
+        pub fn or_insert(self, default: V) -> &'a mut V {
+            self.or_insert_with(|| default)
+        }
+
+        pub fn or_insert_with(self, default: impl FnOnce() -> V) -> &'a mut V {
+            match self {
+                Entry::Occupied(e) => e.into_mut(),
+                Entry::Vacant(e) => e.insert(default()),
+            }
+        }
+
+        pub fn or_default(self) -> &'a mut V
+        where
+            V: Default,
+        {
+            self.or_insert_with(V::default)
+        }
+
+        pub fn and_modify(mut self, f: impl FnOnce(&mut V)) -> Self {
+            if let Entry::Occupied(e) = &mut self {
+                f(e.get_mut());
+            }
+            self
+        }
+    }
+
+    impl<'a, K: IndexKey, V> OccupiedEntry<'a, K, V> {
+        pub fn get(&self) -> &V {
+            self.map.data[self.index].as_ref().unwrap()
+        }
+
+        pub fn get_mut(&mut self) -> &mut V {
+            self.map.data[self.index].as_mut().unwrap()
+        }
+
+        pub fn into_mut(self) -> &'a mut V {
+            self.map.data[self.index].as_mut().unwrap()
+        }
+
+        pub fn insert(&mut self, v: V) -> V {
+            std::mem::replace(self.get_mut(), v)
+        }
+
+        pub fn remove(self) -> V {
+            self.map.remove(K::from_index(self.index)).unwrap()
+        }
+    }
+
+    impl<'a, K: IndexKey, V> VacantEntry<'a, K, V> {
+        pub fn insert(self, v: V) -> &'a mut V {
+            self.map.ensure_capacity(self.index + 1);
+            self.map.len += 1;
+            self.map.data[self.index].insert(v)
+        }
+    }
+
+    pub struct IntoIter<K: IndexKey, V>(
         <Vec<Option<V>> as IntoIterator>::IntoIter,
         usize,
         PhantomData<K>,
     );
 
-    impl<K: Key, V> Iterator for IntoIter<K, V> {
+    impl<K: IndexKey, V> Iterator for IntoIter<K, V> {
         type Item = (K, V);
 
         fn next(&mut self) -> Option<Self::Item> {
@@ -278,17 +684,17 @@ pub mod index_map {
                 .flat_map(|(i, v)| v.map(|v| (i, v)))
                 .next()?;
             self.1 += diff + 1;
-            Some(((self.1 - 1).try_into().unwrap(), v))
+            Some((K::from_index(self.1 - 1), v))
         }
     }
 
-    pub struct Iter<'a, K: Key, V>(
+    pub struct Iter<'a, K: IndexKey, V>(
         <&'a [Option<V>] as IntoIterator>::IntoIter,
         usize,
         PhantomData<K>,
     );
 
-    impl<'a, K: Key, V> Iterator for Iter<'a, K, V> {
+    impl<'a, K: IndexKey, V> Iterator for Iter<'a, K, V> {
         type Item = (K, &'a V);
 
         fn next(&mut self) -> Option<Self::Item> {
@@ -299,17 +705,17 @@ pub mod index_map {
                 .flat_map(|(i, v)| v.as_ref().map(|v| (i, v)))
                 .next()?;
             self.1 += diff + 1;
-            Some(((self.1 - 1).try_into().unwrap(), v))
+            Some((K::from_index(self.1 - 1), v))
         }
     }
 
-    pub struct IterMut<'a, K: Key, V>(
+    pub struct IterMut<'a, K: IndexKey, V>(
         <&'a mut [Option<V>] as IntoIterator>::IntoIter,
         usize,
         PhantomData<K>,
     );
 
-    impl<'a, K: Key, V> Iterator for IterMut<'a, K, V> {
+    impl<'a, K: IndexKey, V> Iterator for IterMut<'a, K, V> {
         type Item = (K, &'a mut V);
 
         fn next(&mut self) -> Option<Self::Item> {
@@ -320,11 +726,11 @@ pub mod index_map {
                 .flat_map(|(i, v)| v.as_mut().map(|v| (i, v)))
                 .next()?;
             self.1 += diff + 1;
-            Some(((self.1 - 1).try_into().unwrap(), v))
+            Some((K::from_index(self.1 - 1), v))
         }
     }
 
-    impl<K: Key, V> IntoIterator for IndexMap<K, V> {
+    impl<K: IndexKey, V> IntoIterator for IndexMap<K, V> {
         type Item = (K, V);
         type IntoIter = IntoIter<K, V>;
 
@@ -333,7 +739,7 @@ pub mod index_map {
         }
     }
 
-    impl<'a, K: Key, V> IntoIterator for &'a IndexMap<K, V> {
+    impl<'a, K: IndexKey, V> IntoIterator for &'a IndexMap<K, V> {
         type Item = (K, &'a V);
         type IntoIter = Iter<'a, K, V>;
 
@@ -342,7 +748,7 @@ pub mod index_map {
         }
     }
 
-    impl<'a, K: Key, V> IntoIterator for &'a mut IndexMap<K, V> {
+    impl<'a, K: IndexKey, V> IntoIterator for &'a mut IndexMap<K, V> {
         type Item = (K, &'a mut V);
         type IntoIter = IterMut<'a, K, V>;
 
@@ -355,6 +761,10 @@ pub mod index_map {
     mod tests {
         use super::*;
 
+        #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+        struct Id(u16);
+        crate::index_key!(Id);
+
         #[test]
         fn test_into_iter() {
             let mut map = IndexMap::new();
@@ -364,24 +774,53 @@ pub mod index_map {
             let entries: Vec<_> = map.into_iter().collect();
             assert_eq!(entries, [(2u32, "Hello"), (3u32, ", "), (5u32, "World")]);
         }
-    }
-}
-
-mod vec_math_ext {
-    use num_traits::PrimInt;
 
-    pub fn vec2_hamming_dist<S: PrimInt>([a0, a1]: [S; 2], [b0, b1]: [S; 2]) -> S {
-        (if a0 > b0 { a0 - b0 } else { b0 - a0 }) + (if a1 > b1 { a1 - b1 } else { b1 - a1 })
-    }
+        #[test]
+        fn test_insert_remove() {
+            let mut map = IndexMap::with_capacity(8);
+            assert_eq!(map.insert(2u32, 'a'), None);
+            assert_eq!(map.insert(6u32, 'b'), None);
+            assert_eq!(map.insert(2u32, 'c'), Some('a'));
+            assert_eq!(map.len(), 2);
+            map[6] = 'd';
+            assert_eq!(map.keys().collect::<Vec<_>>(), [2, 6]);
+            assert_eq!(map.remove(6), Some('d'));
+            assert_eq!(map.remove(6), None);
+            assert_eq!(map.remove(100), None);
+            assert_eq!(map.len(), 1);
+            assert_eq!(map, IndexMap::from_iter([(2, 'c')]));
+            assert_eq!(map.remove(2), Some('c'));
+            assert!(map.is_empty());
+            assert_eq!(map, IndexMap::new());
+        }
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
+        #[test]
+        fn test_entry() {
+            let mut map = IndexMap::<u8, Vec<u8>>::new();
+            map.entry(3).or_default().push(1);
+            map.entry(3).or_insert_with(|| vec![5]).push(2);
+            *map.entry(1).and_modify(|v| v.push(0)).or_insert(vec![9]) = vec![7];
+            assert_eq!(map.len(), 2);
+            assert_eq!(map[3], [1, 2]);
+            assert_eq!(map[1], [7]);
+            match map.entry(3) {
+                Entry::Occupied(e) => assert_eq!(e.remove(), [1, 2]),
+                Entry::Vacant(_) => unreachable!(),
+            }
+            assert_eq!(map.len(), 1);
+            for (_, v) in map.iter_mut() {
+                v.push(8);
+            }
+            assert_eq!(map[1], [7, 8]);
+        }
 
         #[test]
-        fn test_hamming_dist() {
-            assert_eq!(vec2_hamming_dist::<i32>([5, 7], [-1, 2]), 11i32);
-            assert_eq!(vec2_hamming_dist::<u32>([5, 7], [7, 2]), 7u32);
+        fn test_newtype_key() {
+            let mut map = IndexMap::new();
+            map.insert(Id(4), "four");
+            map.insert(Id(1), "one");
+            assert_eq!(map[Id(4)], "four");
+            assert_eq!(map.keys().collect::<Vec<_>>(), [Id(1), Id(4)]);
         }
     }
 }
@@ -389,44 +828,194 @@ mod vec_math_ext {
 mod lin_alg {
     use num_traits::ToPrimitive;
     use std::cmp::{max, min};
+    use std::fmt;
+    use std::fmt::Formatter;
     use std::ops::{Index, IndexMut};
 
+    /// Square matrix where `m[[a, b]] == m[[b, a]]`, storing only one triangle.
+    ///
+    /// With `DIAGONAL = false` the diagonal isn't stored either and always reads as
+    /// `T::default()`. Use it through [`SymmetricalMatrix`] or [`DistanceMatrix`].
     #[derive(Clone, Eq, PartialEq, Debug, Default)]
-    pub struct SymmetricalMatrix<T> {
+    pub struct Symmetrical<T, const DIAGONAL: bool> {
         data: Vec<T>,
         size: usize,
+        /// returned for the diagonal of a [`DistanceMatrix`].
+        zero: T,
     }
 
-    impl<T: Default + Clone> SymmetricalMatrix<T> {
-        pub fn new(size: usize) -> Self {
+    pub type SymmetricalMatrix<T> = Symmetrical<T, true>;
+
+    /// Symmetrical matrix with a zero diagonal, like the distances between the nodes of a graph.
+    pub type DistanceMatrix<T> = Symmetrical<T, false>;
+
+    #[inline]
+    fn stored_count(size: usize, diagonal: bool) -> usize {
+        if diagonal {
+            size * (size + 1) / 2
+        } else {
+            size * size.saturating_sub(1) / 2
+        }
+    }
+
+    impl<T: Default + Clone, const DIAGONAL: bool> Symmetrical<T, DIAGONAL> {
+        fn new_generic(size: usize) -> Self {
             Self {
-                data: vec![T::default(); size * (size + 1) / 2],
+                data: vec![T::default(); stored_count(size, DIAGONAL)],
                 size,
+                zero: T::default(),
+            }
+        }
+    }
+
+    impl<T: Default, const DIAGONAL: bool> Symmetrical<T, DIAGONAL> {
+        fn from_fn_generic(size: usize, mut f: impl FnMut(usize, usize) -> T) -> Self {
+            let mut data = Vec::with_capacity(stored_count(size, DIAGONAL));
+            for a in 0..size {
+                let first = if DIAGONAL { a } else { a + 1 };
+                for b in first..size {
+                    data.push(f(a, b));
+                }
             }
+            Self {
+                data,
+                size,
+                zero: T::default(),
+            }
+        }
+    }
+
+    // the constructors are on the concrete aliases, so `SymmetricalMatrix::new(5)` doesn't need
+    // any type annotations.
+
+    impl<T: Default + Clone> SymmetricalMatrix<T> {
+        pub fn new(size: usize) -> Self {
+            Self::new_generic(size)
         }
     }
 
+    impl<T: Default> SymmetricalMatrix<T> {
+        /// `f(a, b)` is called once for every stored cell, with `a <= b`.
+        pub fn from_fn(size: usize, f: impl FnMut(usize, usize) -> T) -> Self {
+            Self::from_fn_generic(size, f)
+        }
+    }
+
+    impl<T: Default + Clone> DistanceMatrix<T> {
+        pub fn new(size: usize) -> Self {
+            Self::new_generic(size)
+        }
+    }
+
+    impl<T: Default> DistanceMatrix<T> {
+        /// `f(a, b)` is called once for every pair with `a < b`.
+        pub fn from_fn(size: usize, f: impl FnMut(usize, usize) -> T) -> Self {
+            Self::from_fn_generic(size, f)
+        }
+    }
+
+    /// Index of `[x, y]` in the upper triangle including the diagonal, `None` if out of bounds.
     #[inline]
-    fn idx(size: usize, [x, y]: [impl ToPrimitive; 2]) -> usize {
-        let x = x.to_usize().unwrap();
-        let y = y.to_usize().unwrap();
+    fn idx(size: usize, [x, y]: [impl ToPrimitive; 2]) -> Option<usize> {
+        let x = x.to_usize()?;
+        let y = y.to_usize()?;
+        if x >= size || y >= size {
+            return None;
+        }
         let [x, y] = [max(x, y), min(x, y)];
-        y * size - if y == 0 { 0 } else { y * (y - 1) / 2 } + x - y
+        Some(y * size - if y == 0 { 0 } else { y * (y - 1) / 2 } + x - y)
     }
 
-    impl<T, S: ToPrimitive> Index<[S; 2]> for SymmetricalMatrix<T> {
+    impl<T, const DIAGONAL: bool> Symmetrical<T, DIAGONAL> {
+        #[inline]
+        pub fn size(&self) -> usize {
+            self.size
+        }
+
+        /// `Some(None)` is the diagonal of a matrix that doesn't store it.
+        #[inline]
+        fn data_idx(&self, [x, y]: [impl ToPrimitive; 2]) -> Option<Option<usize>> {
+            let (x, y) = (x.to_usize()?, y.to_usize()?);
+            if DIAGONAL {
+                return idx(self.size, [x, y]).map(Some);
+            }
+            if x >= self.size || y >= self.size {
+                None
+            } else if x == y {
+                Some(None)
+            } else {
+                // the strict upper triangle of size n is the full one of size n - 1, shifted by
+                // one column.
+                Some(idx(self.size - 1, [max(x, y) - 1, min(x, y)]))
+            }
+        }
+
+        #[inline]
+        pub fn get(&self, index: [impl ToPrimitive; 2]) -> Option<&T> {
+            Some(match self.data_idx(index)? {
+                Some(i) => &self.data[i],
+                None => &self.zero,
+            })
+        }
+
+        /// `None` outside the matrix, and on the diagonal of a [`DistanceMatrix`].
+        #[inline]
+        pub fn get_mut(&mut self, index: [impl ToPrimitive; 2]) -> Option<&mut T> {
+            let i = self.data_idx(index)??;
+            Some(&mut self.data[i])
+        }
+
+        pub fn row(&self, i: usize) -> impl ExactSizeIterator<Item = &T> {
+            assert!(i < self.size);
+            (0..self.size).map(move |j| &self[[i, j]])
+        }
+
+        pub fn rows(&self) -> impl ExactSizeIterator<Item = impl ExactSizeIterator<Item = &T>> {
+            (0..self.size).map(|i| self.row(i))
+        }
+    }
+
+    impl<T, S: ToPrimitive, const DIAGONAL: bool> Index<[S; 2]> for Symmetrical<T, DIAGONAL> {
         type Output = T;
 
         fn index(&self, index: [S; 2]) -> &Self::Output {
-            let i = idx(self.size, index);
-            &self.data[i]
+            self.get(index).expect("index out of bounds")
         }
     }
 
-    impl<T, S: ToPrimitive> IndexMut<[S; 2]> for SymmetricalMatrix<T> {
+    impl<T, S: ToPrimitive, const DIAGONAL: bool> IndexMut<[S; 2]> for Symmetrical<T, DIAGONAL> {
         fn index_mut(&mut self, index: [S; 2]) -> &mut Self::Output {
-            let i = idx(self.size, index);
-            &mut self.data[i]
+            self.get_mut(index)
+                .expect("index out of bounds, or on the diagonal of a distance matrix")
+        }
+    }
+
+    /// Prints the full square with right-aligned columns.
+    impl<T: fmt::Display, const DIAGONAL: bool> fmt::Display for Symmetrical<T, DIAGONAL> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            let cells: Vec<Vec<String>> = self
+                .rows()
+                .map(|row| row.map(ToString::to_string).collect())
+                .collect();
+            // padding counts chars, so the width has to as well
+            let width = cells
+                .iter()
+                .flatten()
+                .map(|c| c.chars().count())
+                .max()
+                .unwrap_or(0);
+            for (i, row) in cells.iter().enumerate() {
+                if i > 0 {
+                    writeln!(f)?;
+                }
+                for (j, cell) in row.iter().enumerate() {
+                    if j > 0 {
+                        f.write_str(" ")?;
+                    }
+                    write!(f, "{cell:>width$}")?;
+                }
+            }
+            Ok(())
         }
     }
 
@@ -436,10 +1025,12 @@ mod lin_alg {
 
         #[test]
         fn test_idx() {
-            assert_eq!(idx(5, [2, 1]), 6);
-            assert_eq!(idx(5, [1, 2]), 6);
-            assert_eq!(idx(5, [0, 0]), 0);
-            assert_eq!(idx(5, [4, 4]), 14);
+            assert_eq!(idx(5, [2, 1]), Some(6));
+            assert_eq!(idx(5, [1, 2]), Some(6));
+            assert_eq!(idx(5, [0, 0]), Some(0));
+            assert_eq!(idx(5, [4, 4]), Some(14));
+            assert_eq!(idx(5, [5, 4]), None);
+            assert_eq!(idx(5, [-1, 4]), None);
         }
 
         #[test]
@@ -450,6 +1041,34 @@ mod lin_alg {
             assert_eq!(mat[[0, 4]], 69);
             assert_eq!(mat[[4, 0]], 69);
             assert_eq!(mat[[1, 1]], 5);
+            assert_eq!(mat.get([-1, 0]), None);
+            assert_eq!(mat.get([0, 5]), None);
+        }
+
+        #[test]
+        fn test_from_fn() {
+            let mat = SymmetricalMatrix::from_fn(3, |a, b| a * 10 + b);
+            assert_eq!(mat.size(), 3);
+            assert!(mat.row(1).eq(&[1, 11, 12]));
+            assert_eq!(mat.to_string(), " 0  1  2\n 1 11 12\n 2 12 22");
+            let mat = SymmetricalMatrix::from_fn(2, |a, b| if a == b { "∞" } else { "12" });
+            assert_eq!(mat.to_string(), " ∞ 12\n12  ∞");
+        }
+
+        #[test]
+        fn test_distance_matrix() {
+            let mut calls = 0;
+            let mut mat = DistanceMatrix::from_fn(4, |a, b| {
+                calls += 1;
+                b - a
+            });
+            assert_eq!(calls, 6);
+            assert_eq!(mat[[3, 0]], 3);
+            assert_eq!(mat[[2, 2]], 0);
+            assert_eq!(mat.get_mut([2, 2]), None);
+            mat[[1, 2]] = 7;
+            assert!(mat.row(2).eq(&[2, 7, 0, 1]));
+            assert_eq!(mat.to_string(), "0 1 2 3\n1 0 7 2\n2 7 0 1\n3 2 1 0");
         }
     }
 }
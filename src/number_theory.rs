@@ -0,0 +1,107 @@
+//! Modular arithmetic for puzzles which are secretly systems of congruences.
+
+use num_traits::{PrimInt, Signed};
+
+/// `(g, x, y)` with `g = gcd(a, b) = a*x + b*y` and `g >= 0`.
+pub fn ext_gcd<T: PrimInt + Signed>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::one(), T::zero());
+    let (mut old_y, mut y) = (T::zero(), T::one());
+    while !r.is_zero() {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r.is_negative() {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// `x` in `0..m` with `a*x ≡ 1 (mod m)`, if `a` and `m` are coprime.
+pub fn mod_inverse<T: PrimInt + Signed>(a: T, m: T) -> Option<T> {
+    let (g, x, _) = ext_gcd(a, m);
+    let m = m.abs();
+    g.is_one().then(|| {
+        let r = x % m;
+        if r.is_negative() { r + m } else { r }
+    })
+}
+
+/// `x ≡ residue (mod modulus)`. The residue doesn't need to be in `0..modulus`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Congruence {
+    pub residue: i64,
+    pub modulus: i64,
+}
+
+impl Congruence {
+    pub fn new(residue: i64, modulus: i64) -> Self {
+        assert!(modulus > 0, "modulus has to be positive, got {modulus}");
+        Self { residue, modulus }
+    }
+}
+
+/// Solves a system of congruences with the Chinese remainder theorem. The moduli don't need to
+/// be coprime. Returns the smallest non-negative solution together with the lcm of all moduli,
+/// which all other solutions differ by, or `None` if the congruences contradict each other.
+///
+/// Panics if the lcm of the moduli doesn't fit into an `i64`.
+pub fn crt(congruences: impl IntoIterator<Item = Congruence>) -> Option<Congruence> {
+    let mut result = (0i128, 1i128);
+    for c in congruences {
+        let (r1, m1) = result;
+        let (r2, m2) = (c.residue as i128, c.modulus as i128);
+        // x = r1 + m1*k, so m1*k ≡ r2 - r1 (mod m2), which only has a solution if g divides it.
+        let (g, inv, _) = ext_gcd(m1, m2);
+        let diff = r2 - r1;
+        if diff % g != 0 {
+            return None;
+        }
+        let m2_g = m2 / g;
+        let k = (diff / g % m2_g * inv).rem_euclid(m2_g);
+        let lcm = m1 * m2_g;
+        assert!(
+            lcm <= i64::MAX as i128,
+            "lcm of the moduli overflows an i64"
+        );
+        result = ((r1 + m1 * k).rem_euclid(lcm), lcm);
+    }
+    Some(Congruence::new(result.0 as i64, result.1 as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ext_gcd() {
+        assert_eq!(ext_gcd(240, 46), (2, -9, 47));
+        assert_eq!(ext_gcd(-4i32, 6), (2, 1, 1));
+        assert_eq!(ext_gcd(0i64, 0), (0, 1, 0));
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(4, 6), None);
+    }
+
+    #[test]
+    fn test_crt() {
+        let c = Congruence::new;
+        assert_eq!(
+            crt([c(2, 3), c(3, 5), c(2, 7)]),
+            Some(Congruence::new(23, 105))
+        );
+        // not coprime, but consistent
+        assert_eq!(crt([c(1, 4), c(3, 6)]), Some(Congruence::new(9, 12)));
+        // not coprime, and contradicting
+        assert_eq!(crt([c(1, 4), c(2, 6)]), None);
+        assert_eq!(crt([c(-1, 5)]), Some(Congruence::new(4, 5)));
+        assert_eq!(crt([]), Some(Congruence::new(0, 1)));
+    }
+}
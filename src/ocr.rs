@@ -0,0 +1,215 @@
+//! Reads the block letters that some puzzles draw with lit pixels, so their answers can be
+//! compared as text instead of by eye.
+//!
+//! Glyphs are told apart by the empty columns between them, and are compared with the empty
+//! border trimmed away, so it doesn't matter where the text sits on the screen. Glyphs touching
+//! each other can't be read.
+
+use crate::Grid;
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+/// Letters of the font most puzzles use: 6 pixels high, usually 4 wide, and one empty column
+/// between letters. Only the letters that have been seen in puzzles are known.
+const AOC_4X6: [(char, &str); 18] = [
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', ".###\n..#.\n..#.\n..#.\n..#.\n.###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Font {
+    glyphs: Vec<(char, Grid<bool>)>,
+}
+
+impl Font {
+    /// A font without any glyphs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The 4x6 font of the Advent of Code screens.
+    pub fn aoc_4x6() -> Self {
+        AOC_4X6
+            .into_iter()
+            .fold(Self::new(), |font, (c, pattern)| font.with(c, pattern))
+    }
+
+    /// Adds a glyph drawn with `#` for lit and `.` for dark pixels, one line per row. If two glyphs
+    /// look the same, the first one wins.
+    ///
+    /// Panics if the pattern contains any other characters or rows of different widths.
+    pub fn with(mut self, c: char, pattern: &str) -> Self {
+        let pixels = Grid::parse_with(pattern, |_, p| match p {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .unwrap_or_else(|e| panic!("invalid pattern for '{c}': {e}"));
+        self.glyphs.push((c, trim(&pixels, 0..pixels.width())));
+        self
+    }
+
+    /// Reads all glyphs from left to right. If any of them is unknown, the error has the column
+    /// ranges they were found in.
+    pub fn read(&self, pixels: &Grid<bool>) -> Result<String, UnknownGlyphs> {
+        let mut text = String::new();
+        let mut unknown = Vec::new();
+        for columns in glyph_columns(pixels) {
+            let glyph = trim(pixels, columns.clone());
+            match self.glyphs.iter().find(|(_, g)| *g == glyph) {
+                Some(&(c, _)) => text.push(c),
+                None => {
+                    text.push('?');
+                    unknown.push(columns);
+                }
+            }
+        }
+        if unknown.is_empty() {
+            Ok(text)
+        } else {
+            Err(UnknownGlyphs {
+                text,
+                columns: unknown,
+            })
+        }
+    }
+}
+
+/// The column ranges of the runs of columns with at least one lit pixel.
+fn glyph_columns(pixels: &Grid<bool>) -> Vec<Range<usize>> {
+    let mut runs = Vec::new();
+    let mut start = None;
+    for (x, mut column) in pixels.columns().enumerate() {
+        match (column.any(|&p| p), start) {
+            (true, None) => start = Some(x),
+            (false, Some(s)) => {
+                runs.push(s..x);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        runs.push(s..pixels.width());
+    }
+    runs
+}
+
+/// The pixels in `columns` without the empty rows above and below them, and without empty
+/// columns on either side.
+fn trim(pixels: &Grid<bool>, columns: Range<usize>) -> Grid<bool> {
+    let lit_column = |x: &usize| pixels.column(*x).any(|&p| p);
+    let left = columns.clone().find(lit_column).unwrap_or(columns.start);
+    let right = columns.clone().rfind(lit_column).map_or(left, |x| x + 1);
+    let lit_row = |y: &usize| pixels.row(*y)[left..right].iter().any(|&p| p);
+    let top = (0..pixels.height()).find(lit_row).unwrap_or(0);
+    let bottom = (0..pixels.height()).rfind(lit_row).map_or(top, |y| y + 1);
+    let cells = (top..bottom)
+        .flat_map(|y| pixels.row(y)[left..right].iter().copied())
+        .collect();
+    Grid::from_vec(right - left, cells)
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct UnknownGlyphs {
+    /// Everything that could be read, with a `?` for each unknown glyph.
+    pub text: String,
+    /// Where the unknown glyphs are, as ranges of columns.
+    pub columns: Vec<Range<usize>>,
+}
+
+impl fmt::Display for UnknownGlyphs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown glyphs in '{}', at columns", self.text)?;
+        for (i, columns) in self.columns.iter().enumerate() {
+            let sep = if i == 0 { " " } else { ", " };
+            write!(f, "{sep}{}..{}", columns.start, columns.end)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for UnknownGlyphs {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixels(s: &str) -> Grid<bool> {
+        Grid::parse_with(s, |_, c| Some(c == '#')).unwrap()
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn test_read() {
+        let screen = pixels(
+            r"
+                ........................
+                .#..#.####.#....#.....##
+                .#..#.#....#....#....#..
+                .####.###..#....#....#..
+                .#..#.#....#....#....#..
+                .#..#.#....#....#....#..
+                .#..#.####.####.####..##
+            ",
+        );
+        // the last glyph is cut off at the edge of the screen
+        assert_eq!(
+            Font::aoc_4x6().read(&screen),
+            Err(UnknownGlyphs {
+                text: "HELL?".to_string(),
+                columns: vec![21..24],
+            })
+        );
+        let font = Font::aoc_4x6().with('(', ".##\n#..\n#..\n#..\n#..\n.##");
+        assert_eq!(font.read(&screen), Ok("HELL(".to_string()));
+    }
+
+    #[test]
+    fn test_wide_glyph() {
+        let screen = pixels(
+            r"
+                #...#..##..#..#
+                #...#.#..#.#..#
+                .#.#..#..#.#..#
+                ..#...#..#.#..#
+                ..#...#..#.#..#
+                ..#....##...##.
+            ",
+        );
+        assert_eq!(Font::aoc_4x6().read(&screen), Ok("YOU".to_string()));
+        assert_eq!(
+            Font::aoc_4x6().read(&pixels("....\n....")),
+            Ok(String::new())
+        );
+    }
+
+    #[test]
+    fn test_error_display() {
+        let e = UnknownGlyphs {
+            text: "A?B?".to_string(),
+            columns: vec![5..9, 15..16],
+        };
+        assert_eq!(
+            e.to_string(),
+            "unknown glyphs in 'A?B?', at columns 5..9, 15..16"
+        );
+    }
+}
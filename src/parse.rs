@@ -0,0 +1,313 @@
+//! The error type shared by all input parsers, pointing at the line and column where parsing
+//! went wrong and what was expected there.
+//!
+//! Parsers usually only see part of the input (one line, one comma-separated token). They report
+//! errors relative to that part, and the caller moves them to the right place in the whole input
+//! with [`ParseError::within`]. [`parse_lines`] does that for the common one-item-per-line case.
+
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::{error, fmt};
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ParseError {
+    /// 1-based
+    pub line: usize,
+    /// 1-based, counted in chars
+    pub column: usize,
+    pub expected: Cow<'static, str>,
+    /// The token at the error position, or a description like `end of line`.
+    pub found: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: expected {}, found {}",
+            self.line, self.column, self.expected, self.found
+        )
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Byte offset of `part` within `text`. Panics if `part` isn't a subslice of `text`.
+fn offset_in(text: &str, part: &str) -> usize {
+    let start = text.as_ptr() as usize;
+    let offset = (part.as_ptr() as usize).wrapping_sub(start);
+    assert!(
+        offset <= text.len() && offset + part.len() <= text.len(),
+        "not a subslice of the parsed text"
+    );
+    offset
+}
+
+/// 1-based line and column of the byte `offset` in `text`.
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// What to show as `found`: the word at the start of `rest`, shortened if it is very long.
+fn token(rest: &str) -> String {
+    const MAX_LEN: usize = 20;
+    let line = rest.lines().next().unwrap_or("");
+    if rest.is_empty() {
+        return "end of input".to_string();
+    } else if line.is_empty() {
+        return "end of line".to_string();
+    } else if line.starts_with(char::is_whitespace) {
+        return "whitespace".to_string();
+    }
+    let word = line.split_whitespace().next().unwrap_or(line);
+    if word.chars().count() > MAX_LEN {
+        let short: String = word.chars().take(MAX_LEN).collect();
+        format!("'{short}...'")
+    } else {
+        format!("'{word}'")
+    }
+}
+
+impl ParseError {
+    pub fn new(
+        line: usize,
+        column: usize,
+        expected: impl Into<Cow<'static, str>>,
+        found: impl Into<String>,
+    ) -> Self {
+        Self {
+            line,
+            column,
+            expected: expected.into(),
+            found: found.into(),
+        }
+    }
+
+    /// Error at the start of `rest`, which has to be a subslice of `text` (usually what was left
+    /// of it when parsing got stuck).
+    pub fn at(text: &str, rest: &str, expected: impl Into<Cow<'static, str>>) -> Self {
+        let (line, column) = line_column(text, offset_in(text, rest));
+        Self::new(line, column, expected, token(rest))
+    }
+
+    /// Error at the `char_index`th character of `text`.
+    pub fn at_char(text: &str, char_index: usize, expected: impl Into<Cow<'static, str>>) -> Self {
+        let offset = text
+            .char_indices()
+            .nth(char_index)
+            .map_or(text.len(), |(i, _)| i);
+        Self::at(text, &text[offset..], expected)
+    }
+
+    /// Moves an error that is relative to `part` to the corresponding position in `text`, of
+    /// which `part` has to be a subslice.
+    pub fn within(self, text: &str, part: &str) -> Self {
+        let (line, column) = line_column(text, offset_in(text, part));
+        Self {
+            column: if self.line == 1 {
+                column + self.column - 1
+            } else {
+                self.column
+            },
+            line: line + self.line - 1,
+            ..self
+        }
+    }
+}
+
+/// Parses every non-blank line of `input` with `f`, moving errors to the line they occurred in.
+pub fn parse_lines<'i, T>(
+    input: &'i str,
+    mut f: impl FnMut(&'i str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    input
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| f(l).map_err(|e| e.within(input, l)))
+        .collect()
+}
+
+/// Parses all of `input` apart from surrounding whitespace as a single value, like a number.
+pub fn parse_value<T: FromStr>(
+    input: &str,
+    expected: impl Into<Cow<'static, str>>,
+) -> Result<T, ParseError> {
+    let value = input.trim();
+    value
+        .parse()
+        .map_err(|_| ParseError::at(input, value, expected))
+}
+
+/// nom error which keeps track of what was expected, either from the innermost
+/// [`nom::error::context`] or from the kind of parser that failed.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct NomError<'i> {
+    pub rest: &'i str,
+    pub expected: Cow<'static, str>,
+    has_context: bool,
+}
+
+pub type IResult<'i, T> = nom::IResult<&'i str, T, NomError<'i>>;
+
+fn describe(kind: nom::error::ErrorKind) -> Cow<'static, str> {
+    use nom::error::ErrorKind;
+    match kind {
+        ErrorKind::Digit => "a number",
+        ErrorKind::Alpha | ErrorKind::AlphaNumeric => "a letter",
+        ErrorKind::Space | ErrorKind::MultiSpace => "whitespace",
+        ErrorKind::Char => "a character",
+        ErrorKind::Eof => "end of line",
+        ErrorKind::Tag => "a keyword",
+        _ => return kind.description().to_lowercase().into(),
+    }
+    .into()
+}
+
+impl<'i> nom::error::ParseError<&'i str> for NomError<'i> {
+    fn from_error_kind(rest: &'i str, kind: nom::error::ErrorKind) -> Self {
+        Self {
+            rest,
+            expected: describe(kind),
+            has_context: false,
+        }
+    }
+
+    fn append(_: &'i str, _: nom::error::ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn from_char(rest: &'i str, c: char) -> Self {
+        Self {
+            rest,
+            expected: format!("'{c}'").into(),
+            has_context: false,
+        }
+    }
+
+    /// Of all failed alternatives, keep the one which got furthest.
+    fn or(self, other: Self) -> Self {
+        if other.rest.len() <= self.rest.len() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl<'i> nom::error::ContextError<&'i str> for NomError<'i> {
+    fn add_context(_: &'i str, ctx: &'static str, other: Self) -> Self {
+        if other.has_context {
+            return other;
+        }
+        Self {
+            expected: ctx.into(),
+            has_context: true,
+            ..other
+        }
+    }
+}
+
+impl<'i, E> nom::error::FromExternalError<&'i str, E> for NomError<'i> {
+    fn from_external_error(rest: &'i str, kind: nom::error::ErrorKind, _: E) -> Self {
+        nom::error::ParseError::from_error_kind(rest, kind)
+    }
+}
+
+impl NomError<'_> {
+    pub fn into_parse_error(self, text: &str) -> ParseError {
+        ParseError::at(text, self.rest, self.expected)
+    }
+}
+
+/// Runs `parser` on all of `text`, failing if anything is left over.
+pub fn parse_all<'i, T>(
+    text: &'i str,
+    mut parser: impl nom::Parser<&'i str, Output = T, Error = NomError<'i>>,
+) -> Result<T, ParseError> {
+    match parser.parse(text) {
+        Ok(("", result)) => Ok(result),
+        Ok((rest, _)) => Err(ParseError::at(text, rest, "end of line")),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(e.into_parse_error(text)),
+        Err(nom::Err::Incomplete(_)) => {
+            Err(ParseError::at(text, &text[text.len()..], "more input"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::Parser;
+    use nom::bytes::complete::tag;
+    use nom::character::complete::{space1, u32};
+    use nom::error::context;
+
+    #[test]
+    fn test_at() {
+        let text = "first line\nsecond lime\n";
+        let e = ParseError::at(text, &text[18..], "'line'");
+        assert_eq!(e, ParseError::new(2, 8, "'line'", "'lime'"));
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 8: expected 'line', found 'lime'"
+        );
+        assert_eq!(ParseError::at(text, &text[10..], "x").found, "end of line");
+        assert_eq!(ParseError::at(text, &text[23..], "x").found, "end of input");
+        assert_eq!(
+            ParseError::at_char("äöü x", 4, "y"),
+            ParseError::new(1, 5, "y", "'x'")
+        );
+    }
+
+    #[test]
+    fn test_within() {
+        let text = "a, b\nc, d, e";
+        let part = &text[8..9];
+        let e = ParseError::at(part, part, "f").within(text, part);
+        assert_eq!((e.line, e.column), (2, 4));
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let text = "1\n2\n\n3x\n";
+        let result = parse_lines(text, |l| {
+            l.parse::<u32>()
+                .map_err(|_| ParseError::at_char(l, 1, "a number"))
+        });
+        assert_eq!(result, Err(ParseError::new(4, 2, "a number", "'x'")));
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value::<u32>(" 12\n", "a number"), Ok(12));
+        assert_eq!(
+            parse_value::<u32>("\n 1 2\n", "a number"),
+            Err(ParseError::new(2, 2, "a number", "'1'"))
+        );
+    }
+
+    #[test]
+    fn test_nom() {
+        fn bot(s: &str) -> IResult<'_, u32> {
+            let (s, _) = (tag("bot"), space1).parse(s)?;
+            context("a bot id", u32).parse(s)
+        }
+        assert_eq!(parse_all("bot 12", bot), Ok(12));
+        assert_eq!(
+            parse_all("bot x", bot),
+            Err(ParseError::new(1, 5, "a bot id", "'x'"))
+        );
+        assert_eq!(
+            parse_all("bot 1 2", bot),
+            Err(ParseError::new(1, 6, "end of line", "whitespace"))
+        );
+        assert_eq!(
+            parse_all("robot 1", bot),
+            Err(ParseError::new(1, 1, "a keyword", "'robot'"))
+        );
+    }
+}
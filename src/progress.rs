@@ -0,0 +1,177 @@
+//! Progress of long-running loops, shown on stderr so that brute-force searches don't look like
+//! they hang. Nothing is printed unless stderr is a terminal, so output that is piped or captured
+//! stays clean.
+//!
+//! ```no_run
+//! # use aoc2016::progress::Progress;
+//! let mut progress = Progress::bounded("elves", 3_000_000);
+//! for _ in 0..3_000_000 {
+//!     // ...
+//!     progress.inc();
+//! }
+//! progress.finish();
+//! ```
+
+use crate::bench::HumanDuration;
+use std::fmt::Write as _;
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+use std::{io, mem};
+
+/// How many iterations pass between looking at the clock, so [`Progress::inc`] stays cheap
+/// enough for tight loops.
+const CHECK_EVERY: u32 = 1024;
+const REDRAW_EVERY: Duration = Duration::from_millis(200);
+
+#[derive(Clone, Debug)]
+pub struct Progress {
+    label: &'static str,
+    total: Option<u64>,
+    count: u64,
+    until_check: u32,
+    start: Instant,
+    last_draw: Instant,
+    visible: bool,
+    drawn: bool,
+}
+
+impl Progress {
+    /// Counts iterations without knowing how many there will be.
+    pub fn new(label: &'static str) -> Self {
+        Self::with_total(label, None)
+    }
+
+    /// Counts iterations towards `total`, which allows estimating the time left.
+    pub fn bounded(label: &'static str, total: u64) -> Self {
+        Self::with_total(label, Some(total))
+    }
+
+    fn with_total(label: &'static str, total: Option<u64>) -> Self {
+        let now = Instant::now();
+        Self {
+            label,
+            total,
+            count: 0,
+            until_check: CHECK_EVERY,
+            start: now,
+            last_draw: now,
+            visible: io::stderr().is_terminal(),
+            drawn: false,
+        }
+    }
+
+    /// Never prints anything, regardless of where stderr goes.
+    pub fn hidden(mut self) -> Self {
+        self.visible = false;
+        self
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    #[inline]
+    pub fn inc(&mut self) {
+        self.count += 1;
+        self.until_check -= 1;
+        if self.until_check == 0 {
+            self.until_check = CHECK_EVERY;
+            self.tick();
+        }
+    }
+
+    /// Sets the number of iterations done so far, for loops which don't advance one at a time.
+    pub fn set(&mut self, count: u64) {
+        let passed = count.saturating_sub(self.count);
+        self.count = count;
+        if passed >= self.until_check as u64 {
+            self.until_check = CHECK_EVERY;
+            self.tick();
+        } else {
+            self.until_check -= passed as u32;
+        }
+    }
+
+    #[cold]
+    fn tick(&mut self) {
+        if !self.visible {
+            return;
+        }
+        let now = Instant::now();
+        if now - self.last_draw >= REDRAW_EVERY {
+            self.last_draw = now;
+            self.drawn = true;
+            let line = self.render(now - self.start);
+            let _ = write!(io::stderr().lock(), "\r{line}\x1b[K");
+        }
+    }
+
+    /// The status line, e.g. `elves: 1200000/3000000 (40.0%), 2.1M/s, 861.4ms left`.
+    fn render(&self, elapsed: Duration) -> String {
+        let rate = self.count as f64 / elapsed.as_secs_f64().max(1e-9);
+        let mut line = format!("{}: {}", self.label, self.count);
+        if let Some(total) = self.total {
+            let fraction = self.count as f64 / total.max(1) as f64;
+            write!(line, "/{total} ({:.1}%)", fraction * 100.0).unwrap();
+        }
+        write!(line, ", {}/s", Si(rate)).unwrap();
+        if let Some(total) = self.total
+            && rate > 0.0
+        {
+            let left = total.saturating_sub(self.count) as f64 / rate;
+            write!(
+                line,
+                ", {} left",
+                HumanDuration(Duration::from_secs_f64(left.min(1e9)))
+            )
+            .unwrap();
+        }
+        line
+    }
+
+    /// Removes the status line again. Also happens on drop.
+    pub fn finish(self) {}
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if mem::take(&mut self.drawn) {
+            let _ = write!(io::stderr().lock(), "\r\x1b[K");
+        }
+    }
+}
+
+/// A rate with an SI suffix, like `2.1M`.
+struct Si(f64);
+
+impl std::fmt::Display for Si {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            v if v >= 1e9 => write!(f, "{:.1}G", v / 1e9),
+            v if v >= 1e6 => write!(f, "{:.1}M", v / 1e6),
+            v if v >= 1e3 => write!(f, "{:.1}k", v / 1e3),
+            v => write!(f, "{v:.0}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let mut p = Progress::bounded("elves", 3_000_000).hidden();
+        p.set(1_200_000);
+        assert_eq!(
+            p.render(Duration::from_millis(500)),
+            "elves: 1200000/3000000 (40.0%), 2.4M/s, 750.0ms left"
+        );
+        let mut p = Progress::new("hashes").hidden();
+        for _ in 0..2500 {
+            p.inc();
+        }
+        assert_eq!(p.count(), 2500);
+        assert_eq!(p.render(Duration::from_secs(2)), "hashes: 2500, 1.2k/s");
+    }
+}
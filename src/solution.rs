@@ -0,0 +1,571 @@
+//! The common shape of every day: parse the input once, then answer both parts from it.
+//! Each `dNN` binary implements [`Solution`] and its `main` is just [`main`]; the `aoc` binary
+//! pulls all days in and runs any subset of them through [`Day`].
+//!
+//! Inputs are read at runtime, see [`InputSource`]. With `--json`, results are written as one
+//! [`Json`] object per day instead of as text, see [`Report::to_json`].
+
+use crate::Json;
+use crate::bench::{BenchReport, bench};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use std::{env, fmt, fs, io, process};
+
+pub trait Solution {
+    const DAY: u8;
+
+    /// Whatever [`Solution::parse`] produces. May borrow from the input text.
+    type Input<'i>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>>;
+
+    fn part1(input: &Self::Input<'_>) -> impl Display;
+
+    fn part2(input: &Self::Input<'_>) -> impl Display;
+
+    /// Extra data on how the answers came about, like intermediate results. Only computed when
+    /// asked for with `--debug`, as it may repeat the work of both parts.
+    fn debug(_input: &Self::Input<'_>) -> Option<Json> {
+        None
+    }
+}
+
+/// Where a day reads its puzzle input from.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub enum InputSource {
+    /// `dNN/input.txt`
+    #[default]
+    Input,
+    /// `dNN/sample.txt`
+    Sample,
+    File(PathBuf),
+    Stdin,
+}
+
+impl InputSource {
+    /// Parses `[--sample] [FILE|-]`, the arguments every `dNN` binary takes.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut result = None;
+        for arg in args {
+            let source = match arg.as_str() {
+                "--sample" | "-s" => InputSource::Sample,
+                "-" => InputSource::Stdin,
+                _ if arg.starts_with('-') => return Err(format!("unknown option '{arg}'")),
+                _ => InputSource::File(arg.into()),
+            };
+            if result.replace(source).is_some() {
+                return Err("only one input can be given".to_string());
+            }
+        }
+        Ok(result.unwrap_or_default())
+    }
+
+    pub fn read(&self, day: u8) -> io::Result<String> {
+        let path = match self {
+            InputSource::Input => input_dir(day).join("input.txt"),
+            InputSource::Sample => input_dir(day).join("sample.txt"),
+            InputSource::File(path) => path.clone(),
+            InputSource::Stdin => {
+                let mut input = String::new();
+                io::stdin().read_to_string(&mut input)?;
+                return Ok(input);
+            }
+        };
+        fs::read_to_string(&path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))
+    }
+}
+
+/// `dNN` in the working directory if there is one, otherwise the one next to `Cargo.toml`.
+pub fn input_dir(day: u8) -> PathBuf {
+    let dir = format!("d{day:02}");
+    if Path::new(&dir).is_dir() {
+        dir.into()
+    } else {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join(dir)
+    }
+}
+
+/// Command line of a `dNN` binary.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Options {
+    pub input: InputSource,
+    /// Write the [`Report`] as JSON instead of printing the answers.
+    pub json: bool,
+    /// Include [`Solution::debug`].
+    pub debug: bool,
+}
+
+impl Options {
+    /// Parses `[--json] [--debug]` and whatever [`InputSource::from_args`] takes.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut rest = Vec::new();
+        for arg in args {
+            match arg.as_str() {
+                "--json" => options.json = true,
+                "--debug" => options.debug = true,
+                _ => rest.push(arg),
+            }
+        }
+        options.input = InputSource::from_args(rest)?;
+        Ok(options)
+    }
+}
+
+/// Answer for a part that has no solution yet.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Unsolved;
+
+impl Display for Unsolved {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("(unsolved)")
+    }
+}
+
+/// Answers of one day together with how long each step took.
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub day: u8,
+    pub part1: String,
+    pub part2: String,
+    pub parse_time: Duration,
+    pub part1_time: Duration,
+    pub part2_time: Duration,
+    /// [`Solution::debug`], if it was asked for and the day has any.
+    pub debug: Option<Json>,
+}
+
+impl Report {
+    pub fn total_time(&self) -> Duration {
+        self.parse_time + self.part1_time + self.part2_time
+    }
+
+    /// ```text
+    /// {"day": 1, "parse_ns": 1200, "parts": [{"part": 1, "answer": "288", "time_ns": 900}, ...],
+    ///  "total_ns": 4100, "debug": ...}
+    /// ```
+    ///
+    /// Answers are always strings. `debug` is left out if there is none.
+    pub fn to_json(&self) -> Json {
+        let part = |part: u8, answer: &str, time: Duration| {
+            Json::object([
+                ("part", Json::from(part)),
+                ("answer", Json::from(answer)),
+                ("time_ns", Json::from(time.as_nanos())),
+            ])
+        };
+        let mut fields = vec![
+            ("day", Json::from(self.day)),
+            ("parse_ns", Json::from(self.parse_time.as_nanos())),
+            (
+                "parts",
+                Json::Array(vec![
+                    part(1, &self.part1, self.part1_time),
+                    part(2, &self.part2, self.part2_time),
+                ]),
+            ),
+            ("total_ns", Json::from(self.total_time().as_nanos())),
+        ];
+        if let Some(debug) = &self.debug {
+            fields.push(("debug", debug.clone()));
+        }
+        Json::object(fields)
+    }
+}
+
+/// Expected answers for one input, as recorded in `dNN/answers.txt`:
+///
+/// ```text
+/// [input]
+/// part1 = 288
+/// part2 = 111
+///
+/// [sample]
+/// part1 = 8
+/// ```
+///
+/// Parts that aren't listed are not checked. Answers spanning several lines are written with `\n`.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Answers {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+/// A part whose answer differs from the recorded one.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Mismatch {
+    pub part: u8,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "part{}: expected {:?}, got {:?}",
+            self.part, self.expected, self.actual
+        )
+    }
+}
+
+impl Answers {
+    /// The answers recorded for `source`, if there are any. Only [`InputSource::Input`] and
+    /// [`InputSource::Sample`] can have recorded answers.
+    pub fn load(day: u8, source: &InputSource) -> Result<Option<Self>, Box<dyn Error>> {
+        let section = match source {
+            InputSource::Input => "input",
+            InputSource::Sample => "sample",
+            InputSource::File(_) | InputSource::Stdin => return Ok(None),
+        };
+        let path = input_dir(day).join("answers.txt");
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("{}: {e}", path.display()).into()),
+        };
+        Self::parse(&text, section).map_err(|e| format!("{}: {e}", path.display()).into())
+    }
+
+    /// Reads `section` out of the contents of an answers file.
+    pub fn parse(text: &str, section: &str) -> Result<Option<Self>, String> {
+        let mut result = None;
+        let mut current = None;
+        for (nr, line) in text.lines().enumerate().map(|(i, l)| (i + 1, l.trim())) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                current = Some(name.trim());
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("line {nr}: expected 'partN = answer'"));
+            };
+            if current != Some(section) {
+                continue;
+            }
+            let answers: &mut Answers = result.get_or_insert_default();
+            let value = Some(value.trim().replace("\\n", "\n"));
+            match key.trim() {
+                "part1" => answers.part1 = value,
+                "part2" => answers.part2 = value,
+                key => return Err(format!("line {nr}: unknown key '{key}'")),
+            }
+        }
+        Ok(result)
+    }
+
+    /// The recorded answer of `part`, which is 1 or 2.
+    pub fn part(&self, part: u8) -> Option<&str> {
+        match part {
+            1 => self.part1.as_deref(),
+            2 => self.part2.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Compares `actual` with the recorded answer of `part`, ignoring trailing whitespace. Parts
+    /// without a recorded answer always match.
+    pub fn check(&self, part: u8, actual: &str) -> Option<Mismatch> {
+        let expected = self.part(part)?.trim_end();
+        let actual = actual.trim_end();
+        (expected != actual).then(|| Mismatch {
+            part,
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        })
+    }
+}
+
+/// Result of [`verify`].
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Verification {
+    /// The parts that had a recorded answer and were run.
+    pub checked: Vec<u8>,
+    pub mismatches: Vec<Mismatch>,
+    /// Parsing and running the checked parts, without the ones that were left out.
+    pub time: Duration,
+}
+
+pub(crate) fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+/// Parses `input` and runs both parts on it, and [`Solution::debug`] too if `debug` is set.
+pub fn solve<S: Solution>(input: &str, debug: bool) -> Result<Report, Box<dyn Error>> {
+    let (parsed, parse_time) = timed(|| S::parse(input));
+    let parsed = parsed?;
+    let (part1, part1_time) = timed(|| S::part1(&parsed).to_string());
+    let (part2, part2_time) = timed(|| S::part2(&parsed).to_string());
+    Ok(Report {
+        day: S::DAY,
+        part1,
+        part2,
+        parse_time,
+        part1_time,
+        part2_time,
+        debug: if debug { S::debug(&parsed) } else { None },
+    })
+}
+
+/// Parses `input` and runs only the parts that have an answer in `answers`, so that a part which
+/// is slow or unsolved doesn't hold up checking the other one.
+pub fn verify<S: Solution>(input: &str, answers: &Answers) -> Result<Verification, Box<dyn Error>> {
+    let (parsed, mut time) = timed(|| S::parse(input));
+    let parsed = parsed?;
+    let mut result = Verification::default();
+    for part in [1, 2] {
+        if answers.part(part).is_none() {
+            continue;
+        }
+        let (actual, part_time) = timed(|| match part {
+            1 => S::part1(&parsed).to_string(),
+            _ => S::part2(&parsed).to_string(),
+        });
+        time += part_time;
+        result.checked.push(part);
+        result.mismatches.extend(answers.check(part, &actual));
+    }
+    result.time = time;
+    Ok(result)
+}
+
+/// Takes the input and whether to include [`Solution::debug`].
+pub type SolveFn = fn(&str, bool) -> Result<Report, Box<dyn Error>>;
+
+/// Takes the input and the recorded answers to compare with.
+pub type VerifyFn = fn(&str, &Answers) -> Result<Verification, Box<dyn Error>>;
+
+/// Takes the input and the number of runs.
+pub type BenchFn = fn(&str, usize) -> Result<BenchReport, Box<dyn Error>>;
+
+/// Type-erased handle of a [`Solution`], so that days can be put into a table.
+#[derive(Copy, Clone)]
+pub struct Day {
+    pub day: u8,
+    pub solve: SolveFn,
+    pub verify: VerifyFn,
+    pub bench: BenchFn,
+}
+
+impl Day {
+    pub const fn of<S: Solution>() -> Self {
+        Self {
+            day: S::DAY,
+            solve: solve::<S>,
+            verify: verify::<S>,
+            bench: bench::<S>,
+        }
+    }
+}
+
+impl fmt::Debug for Day {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Day").field("day", &self.day).finish()
+    }
+}
+
+/// Body of the `main` function of each `dNN` binary: `dNN [--sample] [--json] [--debug] [FILE|-]`.
+pub fn main<S: Solution>() {
+    let options = Options::from_args(env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!(
+            "{e}\nusage: d{:02} [--sample] [--json] [--debug] [FILE|-]",
+            S::DAY
+        );
+        process::exit(2);
+    });
+    let input = options.input.read(S::DAY).unwrap_or_else(|e| {
+        eprintln!("could not read input: {e}");
+        process::exit(1);
+    });
+    if options.json {
+        let report = solve::<S>(&input, options.debug).unwrap_or_else(|e| {
+            eprintln!("invalid input: {e}");
+            process::exit(1);
+        });
+        println!("{}", report.to_json());
+        return;
+    }
+    let input = S::parse(&input).unwrap_or_else(|e| {
+        eprintln!("invalid input: {e}");
+        process::exit(1);
+    });
+    print_answer(1, &S::part1(&input).to_string());
+    print_answer(2, &S::part2(&input).to_string());
+    if options.debug {
+        print_debug(S::debug(&input).as_ref());
+    }
+}
+
+/// Prints `PartN: answer`. Answers spanning several lines (like a rendered screen) start on their
+/// own line so they stay aligned.
+pub fn print_answer(part: u8, answer: &str) {
+    if answer.contains('\n') {
+        println!("Part{part}:\n{}", answer.trim_end());
+    } else {
+        println!("Part{part}: {answer}");
+    }
+}
+
+/// Prints `Debug: json`, or that there is nothing to show.
+pub fn print_debug(debug: Option<&Json>) {
+    match debug {
+        Some(debug) => println!("Debug: {debug}"),
+        None => println!("Debug: (none)"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Sum;
+
+    impl Solution for Sum {
+        const DAY: u8 = 0;
+        type Input<'i> = Vec<u32>;
+
+        fn parse(input: &str) -> Result<Self::Input<'_>, Box<dyn Error>> {
+            Ok(input
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()?)
+        }
+
+        fn part1(input: &Self::Input<'_>) -> impl Display {
+            input.iter().sum::<u32>()
+        }
+
+        fn part2(input: &Self::Input<'_>) -> impl Display {
+            input.iter().product::<u32>()
+        }
+
+        fn debug(input: &Self::Input<'_>) -> Option<Json> {
+            Some(input.len().into())
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        let report = (Day::of::<Sum>().solve)("4 5 6", true).unwrap();
+        assert_eq!(report.day, 0);
+        assert_eq!(report.part1, "15");
+        assert_eq!(report.part2, "120");
+        assert_eq!(report.debug, Some(Json::from(3)));
+        assert_eq!(solve::<Sum>("4 5 6", false).unwrap().debug, None);
+        assert!(solve::<Sum>("4 x", false).is_err());
+    }
+
+    #[test]
+    fn test_answers() {
+        let text = "\
+            # day 0\n\
+            [input]\n\
+            part1 = 6\n\
+            part2 = 6\n\
+            \n\
+            [sample]\n\
+            part2 = a\\nb\n\
+        ";
+        let input = Answers::parse(text, "input").unwrap().unwrap();
+        assert_eq!(input.part1.as_deref(), Some("6"));
+        let sample = Answers::parse(text, "sample").unwrap().unwrap();
+        assert_eq!(sample.part1, None);
+        assert_eq!(sample.part2.as_deref(), Some("a\nb"));
+        assert_eq!(Answers::parse(text, "other"), Ok(None));
+        assert!(Answers::parse("[input]\npart3 = 1", "input").is_err());
+
+        assert_eq!(input.check(1, "6\n"), None);
+        assert_eq!(sample.check(1, "anything"), None);
+        assert_eq!(
+            input.check(2, "7"),
+            Some(Mismatch {
+                part: 2,
+                expected: "6".to_string(),
+                actual: "7".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let verify = Day::of::<Sum>().verify;
+        let answers = Answers {
+            part1: Some("7".to_string()),
+            part2: None,
+        };
+        let result = verify("1 2 3", &answers).unwrap();
+        assert_eq!(result.checked, [1]);
+        assert_eq!(
+            result.mismatches,
+            [Mismatch {
+                part: 1,
+                expected: "7".to_string(),
+                actual: "6".to_string(),
+            }]
+        );
+        let result = verify("1 2 3", &Answers::default()).unwrap();
+        assert_eq!(result.checked, []);
+        assert!(verify("1 x", &answers).is_err());
+    }
+
+    #[test]
+    fn test_to_json() {
+        let report = Report {
+            day: 3,
+            part1: "a\"b".to_string(),
+            part2: "7".to_string(),
+            parse_time: Duration::from_nanos(5),
+            part1_time: Duration::from_nanos(10),
+            part2_time: Duration::from_nanos(20),
+            debug: None,
+        };
+        assert_eq!(
+            report.to_json().to_string(),
+            r#"{"day": 3, "parse_ns": 5, "parts": [{"part": 1, "answer": "a\"b", "time_ns": 10}, {"part": 2, "answer": "7", "time_ns": 20}], "total_ns": 35}"#
+        );
+    }
+
+    #[test]
+    fn test_options() {
+        let args = |a: &[&str]| Options::from_args(a.iter().map(|s| s.to_string()));
+        assert_eq!(
+            args(&["--json", "-s"]),
+            Ok(Options {
+                input: InputSource::Sample,
+                json: true,
+                debug: false,
+            })
+        );
+        assert!(args(&["--json", "--jsno"]).is_err());
+    }
+
+    #[test]
+    fn test_input_source() {
+        let args = |a: &[&str]| InputSource::from_args(a.iter().map(|s| s.to_string()));
+        assert_eq!(args(&[]), Ok(InputSource::Input));
+        assert_eq!(args(&["--sample"]), Ok(InputSource::Sample));
+        assert_eq!(args(&["-"]), Ok(InputSource::Stdin));
+        assert_eq!(
+            args(&["other.txt"]),
+            Ok(InputSource::File("other.txt".into()))
+        );
+        assert!(args(&["--sample", "other.txt"]).is_err());
+        assert!(args(&["--verbose"]).is_err());
+
+        let path = env::temp_dir().join(format!("aoc2016-input-{}.txt", process::id()));
+        fs::write(&path, "R2, L3\n").unwrap();
+        let source = InputSource::File(path.clone());
+        assert_eq!(source.read(1).unwrap(), "R2, L3\n");
+        fs::remove_file(&path).unwrap();
+        let error = source.read(1).unwrap_err();
+        assert!(error.to_string().starts_with(&path.display().to_string()));
+    }
+}
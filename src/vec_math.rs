@@ -0,0 +1,306 @@
+//! Small integer vectors. `[S; 2]` is still what most days use for positions, so everything in
+//! here converts to and from arrays for free.
+
+use num_traits::{PrimInt, Signed};
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Vec2<S> {
+    pub x: S,
+    pub y: S,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Vec3<S> {
+    pub x: S,
+    pub y: S,
+    pub z: S,
+}
+
+#[inline]
+fn abs_diff<S: PrimInt>(a: S, b: S) -> S {
+    if a > b { a - b } else { b - a }
+}
+
+impl<S> Vec2<S> {
+    #[inline]
+    pub const fn new(x: S, y: S) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<S: PrimInt> Vec2<S> {
+    #[inline]
+    pub fn zero() -> Self {
+        Self::new(S::zero(), S::zero())
+    }
+
+    /// Works for unsigned coordinates as well, unlike [`Vec2::manhattan`].
+    #[inline]
+    pub fn manhattan_dist(self, other: Self) -> S {
+        abs_diff(self.x, other.x) + abs_diff(self.y, other.y)
+    }
+
+    #[inline]
+    pub fn chebyshev_dist(self, other: Self) -> S {
+        abs_diff(self.x, other.x).max(abs_diff(self.y, other.y))
+    }
+
+    /// Orthogonal neighbors in the order up, right, down, left (with `y` growing downwards).
+    /// Neighbors that would over- or underflow `S` are skipped, so `[0, 0]` of an unsigned vector
+    /// only has two neighbors.
+    pub fn neighbors(self) -> impl Iterator<Item = Self> {
+        let Self { x, y } = self;
+        let one = S::one();
+        [
+            y.checked_sub(&one).map(|y| Self::new(x, y)),
+            x.checked_add(&one).map(|x| Self::new(x, y)),
+            y.checked_add(&one).map(|y| Self::new(x, y)),
+            x.checked_sub(&one).map(|x| Self::new(x, y)),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// Orthogonal and diagonal neighbors, clockwise starting at the top.
+    pub fn neighbors_with_diagonals(self) -> impl Iterator<Item = Self> {
+        let Self { x, y } = self;
+        let one = S::one();
+        let dec = |v: S| v.checked_sub(&one);
+        let keep = |v: S| Some(v);
+        let inc = |v: S| v.checked_add(&one);
+        [
+            (keep(x), dec(y)),
+            (inc(x), dec(y)),
+            (inc(x), keep(y)),
+            (inc(x), inc(y)),
+            (keep(x), inc(y)),
+            (dec(x), inc(y)),
+            (dec(x), keep(y)),
+            (dec(x), dec(y)),
+        ]
+        .into_iter()
+        .filter_map(|(x, y)| Some(Self::new(x?, y?)))
+    }
+}
+
+impl<S: PrimInt + Signed> Vec2<S> {
+    /// Length in the Manhattan metric.
+    #[inline]
+    pub fn manhattan(self) -> S {
+        self.x.abs() + self.y.abs()
+    }
+
+    /// Rotates by 90° clockwise on screen, i.e. with `y` growing downwards.
+    #[inline]
+    pub fn rotate_cw(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    #[inline]
+    pub fn rotate_ccw(self) -> Self {
+        Self::new(self.y, -self.x)
+    }
+
+    #[inline]
+    pub fn signum(self) -> Self {
+        Self::new(self.x.signum(), self.y.signum())
+    }
+}
+
+impl<S> Vec3<S> {
+    #[inline]
+    pub const fn new(x: S, y: S, z: S) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<S: PrimInt> Vec3<S> {
+    #[inline]
+    pub fn zero() -> Self {
+        Self::new(S::zero(), S::zero(), S::zero())
+    }
+
+    #[inline]
+    pub fn manhattan_dist(self, other: Self) -> S {
+        abs_diff(self.x, other.x) + abs_diff(self.y, other.y) + abs_diff(self.z, other.z)
+    }
+
+    #[inline]
+    pub fn chebyshev_dist(self, other: Self) -> S {
+        abs_diff(self.x, other.x)
+            .max(abs_diff(self.y, other.y))
+            .max(abs_diff(self.z, other.z))
+    }
+
+    /// The six face neighbors, skipping any that would over- or underflow `S`.
+    pub fn neighbors(self) -> impl Iterator<Item = Self> {
+        let Self { x, y, z } = self;
+        let one = S::one();
+        [
+            x.checked_sub(&one).map(|x| Self::new(x, y, z)),
+            x.checked_add(&one).map(|x| Self::new(x, y, z)),
+            y.checked_sub(&one).map(|y| Self::new(x, y, z)),
+            y.checked_add(&one).map(|y| Self::new(x, y, z)),
+            z.checked_sub(&one).map(|z| Self::new(x, y, z)),
+            z.checked_add(&one).map(|z| Self::new(x, y, z)),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl<S: PrimInt + Signed> Vec3<S> {
+    #[inline]
+    pub fn manhattan(self) -> S {
+        self.x.abs() + self.y.abs() + self.z.abs()
+    }
+}
+
+// the operator impls are identical for both sizes, apart from the field list.
+macro_rules! impl_vec_ops {
+    ($V:ident, $N:literal, $($f:ident),+) => {
+        impl<S: Add<Output = S>> Add for $V<S> {
+            type Output = Self;
+
+            #[inline]
+            fn add(self, rhs: Self) -> Self::Output {
+                $V { $($f: self.$f + rhs.$f),+ }
+            }
+        }
+
+        impl<S: Sub<Output = S>> Sub for $V<S> {
+            type Output = Self;
+
+            #[inline]
+            fn sub(self, rhs: Self) -> Self::Output {
+                $V { $($f: self.$f - rhs.$f),+ }
+            }
+        }
+
+        impl<S: Neg<Output = S>> Neg for $V<S> {
+            type Output = Self;
+
+            #[inline]
+            fn neg(self) -> Self::Output {
+                $V { $($f: -self.$f),+ }
+            }
+        }
+
+        impl<S: Mul<Output = S> + Copy> Mul<S> for $V<S> {
+            type Output = Self;
+
+            #[inline]
+            fn mul(self, rhs: S) -> Self::Output {
+                $V { $($f: self.$f * rhs),+ }
+            }
+        }
+
+        impl<S: AddAssign> AddAssign for $V<S> {
+            #[inline]
+            fn add_assign(&mut self, rhs: Self) {
+                $(self.$f += rhs.$f;)+
+            }
+        }
+
+        impl<S: SubAssign> SubAssign for $V<S> {
+            #[inline]
+            fn sub_assign(&mut self, rhs: Self) {
+                $(self.$f -= rhs.$f;)+
+            }
+        }
+
+        impl<S: MulAssign + Copy> MulAssign<S> for $V<S> {
+            #[inline]
+            fn mul_assign(&mut self, rhs: S) {
+                $(self.$f *= rhs;)+
+            }
+        }
+
+        impl<S> From<[S; $N]> for $V<S> {
+            #[inline]
+            fn from([$($f),+]: [S; $N]) -> Self {
+                $V { $($f),+ }
+            }
+        }
+
+        impl<S> From<$V<S>> for [S; $N] {
+            #[inline]
+            fn from(v: $V<S>) -> Self {
+                [$(v.$f),+]
+            }
+        }
+    };
+}
+
+impl_vec_ops!(Vec2, 2, x, y);
+impl_vec_ops!(Vec3, 3, x, y, z);
+
+impl<S> From<(S, S)> for Vec2<S> {
+    #[inline]
+    fn from((x, y): (S, S)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<S> From<Vec2<S>> for (S, S) {
+    #[inline]
+    fn from(v: Vec2<S>) -> Self {
+        (v.x, v.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ops() {
+        let a = Vec2::new(3, -2);
+        let b = Vec2::from([1, 5]);
+        assert_eq!(a + b, Vec2::new(4, 3));
+        assert_eq!(a - b, Vec2::new(2, -7));
+        assert_eq!(-a * 2, Vec2::new(-6, 4));
+        let mut c = a;
+        c += b;
+        c *= 3;
+        assert_eq!(<[i32; 2]>::from(c), [12, 9]);
+        assert_eq!(
+            Vec3::new(1, 2, 3) + Vec3::from([1, 1, 1]),
+            Vec3::new(2, 3, 4)
+        );
+    }
+
+    #[test]
+    fn test_distances() {
+        let a = Vec2::new(5u32, 7);
+        let b = Vec2::new(7u32, 2);
+        assert_eq!(a.manhattan_dist(b), 7);
+        assert_eq!(Vec2::new(5, 7).manhattan_dist(Vec2::new(-1, 2)), 11);
+        assert_eq!(a.chebyshev_dist(b), 5);
+        assert_eq!(Vec2::new(-3, 4).manhattan(), 7);
+        assert_eq!(Vec3::new(1, -2, 3).manhattan_dist(Vec3::zero()), 6);
+        assert_eq!(Vec3::new(1, -2, 3).chebyshev_dist(Vec3::zero()), 3);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        assert_eq!(
+            Vec2::new(0u32, 0).neighbors().collect::<Vec<_>>(),
+            [Vec2::new(1, 0), Vec2::new(0, 1)]
+        );
+        assert_eq!(Vec2::new(4, 4).neighbors().count(), 4);
+        assert_eq!(Vec2::new(0u8, 3).neighbors_with_diagonals().count(), 5);
+        assert_eq!(Vec3::new(0u8, 0, 0).neighbors().count(), 3);
+        assert_eq!(Vec3::new(1, 1, 1).neighbors().count(), 6);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let up = Vec2::new(0, -1);
+        assert_eq!(up.rotate_cw(), Vec2::new(1, 0));
+        assert_eq!(up.rotate_cw().rotate_cw(), Vec2::new(0, 1));
+        assert_eq!(up.rotate_ccw(), Vec2::new(-1, 0));
+        assert_eq!(Vec2::new(3, -7).rotate_cw().rotate_ccw(), Vec2::new(3, -7));
+    }
+}